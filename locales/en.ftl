@@ -0,0 +1,173 @@
+# English
+
+app-read-only = Tasking! (read-only: another Tasking! is using the data file)
+about-title = About Tasking!
+about-description = A simple task manager
+
+## tasks
+
+status-new = New
+status-progress = In progress
+status-stop = Stopped
+status-done = Done
+export-heading = { $status } tasks
+export-failed-title = Export failed
+
+recurrence-daily = Daily
+recurrence-weekly = Weekly
+recurrence-workdays = Weekdays
+recurrence-days = Every { $days }
+recurrence-monthly = Monthly
+recurrence-separator = { ", " }
+day-mon = Mon
+day-tue = Tue
+day-wed = Wed
+day-thu = Thu
+day-fri = Fri
+day-sat = Sat
+day-sun = Sun
+
+## list
+
+new-task-placeholder = Enter a new task
+add = Add
+no-tasks = No tasks yet
+delete = Delete
+delete-confirm = Really?
+clear = Clear
+sort = Tidy
+memo = Memo
+stats = Stats
+back = Back
+
+## details
+
+detail-created = Created { $time }
+detail-completed = Done { $time }
+detail-notes = Notes (Markdown)
+detail-preview = Preview
+detail-tags = Tags
+detail-tags-placeholder = Separate with commas or spaces
+detail-due = Due date
+detail-due-invalid = Enter the date like 2024-01-31
+detail-remind-at = Reminder
+detail-remind-at-invalid = Enter the time like 2024-01-31 15:00
+detail-weekdays = Repeat on
+detail-history = Status history
+detail-no-history = The status has not changed yet
+
+## conflicts and sync
+
+conflict-message = The data file was changed by another app, and the same tasks were changed on both sides.
+conflict-merge = Merge both (mine wins conflicts)
+conflict-theirs = Load the external changes
+conflict-mine = Keep my changes
+conflict-edited = "{ $content }": changed on both sides
+conflict-removed-locally = "{ $content }": removed here, changed on the other side
+conflict-removed-remotely = "{ $content }": changed here, removed on the other side
+sync-conflict-title = Sync found conflicts
+sync-conflict-message = The tasks on this device were kept.
+sync-failed-title = Sync failed
+sync-not-configured = Sync is not configured
+sync-unsupported = Only JSON data files can be synced
+sync-locked = The data file is locked
+sync-remote-unreadable = Could not read the remote tasks
+sync-file-name-invalid = The data file name is not valid
+sync-git-failed = Could not run git: { $error }
+sync-merge-message = Sync: merge
+sync-merge-conflicts-message = Sync: merge ({ $count } conflicts kept as on this device)
+
+## data file
+
+open-failed-title = Could not open the data file
+store-open-failed = Could not open { $file }: { $error }
+
+## change log
+
+change-add = Added #{ $id } { $content }
+change-status = Status #{ $id } { $status }
+change-edit = Edited #{ $id } { $content }
+change-update = Updated #{ $id } { $content }
+change-remove = Removed #{ $id }
+change-clear = Cleared
+change-sort = Tidied
+change-replace = Replaced
+
+## encryption
+
+unlock-message = { $file } is encrypted.\nEnter the passphrase
+unlock-failed = Wrong passphrase
+unlock = Unlock
+
+## statistics
+
+stats-count = { $status } { $count }
+stats-week = { $start }– { $count }
+stats-weekly = Completed per week: { $weeks }
+stats-average = Average from new to done: { $average }
+stats-daily = Completed per day
+duration-minutes = { $minutes } min
+duration-hours = { $hours } h
+duration-days = { $days } d { $hours } h
+
+## pomodoro and reminders
+
+phase-work = Working
+phase-break = On a break
+interrupt = Interrupt
+reminder-title = Reminder
+reminder-snooze = Snooze
+
+## backups
+
+backup-count = { $date }  { $count } tasks
+backup-same = Same as the current tasks
+backup-diff = Restored { $restored } / lost { $lost } / changed { $changed }
+restore = Restore
+no-backups = No backups yet
+
+## menu
+
+menu-file = File
+menu-import = Import...
+menu-export = Export...
+menu-open = Open data file...
+menu-backups = Restore from backup...
+menu-sync = Sync
+menu-settings = Settings...
+menu-quit = Quit
+menu-edit = Edit
+menu-undo = Undo
+menu-redo = Redo
+menu-select-all = Select all
+menu-view = View
+menu-filter = Filter
+menu-all = All
+menu-theme = Theme
+menu-board = Board
+menu-pomodoro = Pomodoro
+menu-help = Help
+
+## settings
+
+theme-light = Light
+theme-dark = Dark
+theme-high-contrast = High contrast
+theme-custom = Custom
+language-system = Follow the system
+sort-manual = As arranged
+sort-status = Status
+sort-created = Created
+sort-due = Due date
+settings-data-file = Data file (empty for the default)
+settings-theme = Theme
+settings-text-size = Text size
+settings-task-text-size = Task text size
+settings-language = Language
+settings-sort = Order
+settings-confirm-delete = Confirm before deleting
+settings-autosave = Seconds before autosave
+settings-save-failed = Could not save the settings
+settings-file-in-use = { $file } is open in another Tasking!
+config-invalid = { $file } is not valid: { $error }
+config-write-failed = Could not write { $file }: { $error }
@@ -0,0 +1,173 @@
+# 日本語
+
+app-read-only = Tasking! (読み取り専用: 他のTasking!が使用中です)
+about-title = Tasking! について
+about-description = シンプルタスク管理アプリ
+
+## タスク
+
+status-new = 新規
+status-progress = 実行中
+status-stop = 停止
+status-done = 完了
+export-heading = { $status }タスク
+export-failed-title = エクスポートできませんでした
+
+recurrence-daily = 毎日
+recurrence-weekly = 毎週
+recurrence-workdays = 平日
+recurrence-days = 毎週{ $days }
+recurrence-monthly = 毎月
+recurrence-separator = ・
+day-mon = 月
+day-tue = 火
+day-wed = 水
+day-thu = 木
+day-fri = 金
+day-sat = 土
+day-sun = 日
+
+## 一覧
+
+new-task-placeholder = 新しいタスクを入力してください
+add = 追加
+no-tasks = タスクはまだありません
+delete = 削除
+delete-confirm = 本当に削除
+clear = クリア
+sort = 整頓
+memo = メモ
+stats = 統計
+back = 戻る
+
+## 詳細
+
+detail-created = 作成 { $time }
+detail-completed = 完了 { $time }
+detail-notes = ノート（Markdown）
+detail-preview = プレビュー
+detail-tags = タグ
+detail-tags-placeholder = カンマかスペースで区切ってください
+detail-due = 期限
+detail-due-invalid = 日付は 2024-01-31 の形で入力してください
+detail-remind-at = リマインダー
+detail-remind-at-invalid = 日時は 2024-01-31 15:00 の形で入力してください
+detail-weekdays = 繰り返す曜日
+detail-history = 状態の履歴
+detail-no-history = 状態はまだ変わっていません
+
+## 競合と同期
+
+conflict-message = データファイルが他のアプリで変更され、同じタスクが両方で変更されています。
+conflict-merge = 両方をマージ（競合は自分の変更）
+conflict-theirs = 外部の変更を読み込む
+conflict-mine = 自分の変更を保持
+conflict-edited = 「{ $content }」: 両方で変更されました
+conflict-removed-locally = 「{ $content }」: こちらで削除、相手側で変更されました
+conflict-removed-remotely = 「{ $content }」: こちらで変更、相手側で削除されました
+sync-conflict-title = 同期で競合がありました
+sync-conflict-message = この端末の内容を残しました。
+sync-failed-title = 同期できませんでした
+sync-not-configured = 同期が設定されていません
+sync-unsupported = 同期できるのは JSON のデータファイルだけです
+sync-locked = データファイルがロックされています
+sync-remote-unreadable = リモートのタスクを読み込めませんでした
+sync-file-name-invalid = データファイル名が正しくありません
+sync-git-failed = git を実行できませんでした: { $error }
+sync-merge-message = 同期: マージ
+sync-merge-conflicts-message = 同期: マージ（競合 { $count } 件はこの端末の内容を保持）
+
+## データファイル
+
+open-failed-title = データファイルを開けませんでした
+store-open-failed = { $file } を開けませんでした: { $error }
+
+## 変更履歴
+
+change-add = 追加 #{ $id } { $content }
+change-status = 状態 #{ $id } { $status }
+change-edit = 編集 #{ $id } { $content }
+change-update = 更新 #{ $id } { $content }
+change-remove = 削除 #{ $id }
+change-clear = クリア
+change-sort = 整頓
+change-replace = 置換
+
+## 暗号化
+
+unlock-message = { $file } は暗号化されています。\nパスフレーズを入力してください
+unlock-failed = パスフレーズが違います
+unlock = 解除
+
+## 統計
+
+stats-count = { $status } { $count }件
+stats-week = { $start }〜 { $count }件
+stats-weekly = 週ごとの完了: { $weeks }
+stats-average = 新規から完了までの平均: { $average }
+stats-daily = 日ごとの完了
+duration-minutes = { $minutes }分
+duration-hours = { $hours }時間
+duration-days = { $days }日 { $hours }時間
+
+## ポモドーロとリマインダー
+
+phase-work = 作業中
+phase-break = 休憩中
+interrupt = 中断
+reminder-title = リマインダー
+reminder-snooze = スヌーズ
+
+## バックアップ
+
+backup-count = { $date }  { $count }件
+backup-same = 現在と同じ内容です
+backup-diff = 復元 { $restored } / 消失 { $lost } / 変更 { $changed }
+restore = 復元
+no-backups = バックアップはまだありません
+
+## メニュー
+
+menu-file = ファイル
+menu-import = インポート...
+menu-export = エクスポート...
+menu-open = データファイルを開く...
+menu-backups = バックアップから復元...
+menu-sync = 同期
+menu-settings = 設定...
+menu-quit = 終了
+menu-edit = 編集
+menu-undo = 元に戻す
+menu-redo = やり直す
+menu-select-all = すべて選択
+menu-view = 表示
+menu-filter = フィルター
+menu-all = すべて
+menu-theme = テーマ
+menu-board = ボード
+menu-pomodoro = ポモドーロ
+menu-help = ヘルプ
+
+## 設定
+
+theme-light = ライト
+theme-dark = ダーク
+theme-high-contrast = ハイコントラスト
+theme-custom = カスタム
+language-system = システムに合わせる
+sort-manual = 並べた順
+sort-status = 状態
+sort-created = 作成日
+sort-due = 期限
+settings-data-file = データファイル（空欄で既定の場所）
+settings-theme = テーマ
+settings-text-size = 文字の大きさ
+settings-task-text-size = タスクの文字の大きさ
+settings-language = 言語
+settings-sort = 並び順
+settings-confirm-delete = 削除の前に確認する
+settings-autosave = 自動保存までの秒数
+settings-save-failed = 設定を保存できませんでした
+settings-file-in-use = { $file } は他の Tasking! で開かれています
+config-invalid = { $file } が正しくありません: { $error }
+config-write-failed = { $file } に書き込めませんでした: { $error }
@@ -0,0 +1,250 @@
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::model::{Recurrence, Task, TaskId, TaskStatus, Tasks};
+use crate::store::Change;
+use crate::TaskState;
+
+/// task mutations
+#[derive(Debug, Clone)]
+pub enum Action {
+    Add(String),
+    SetStatus(TaskId, TaskStatus),
+    Remove(TaskId),
+    Clear,
+    Sort(Vec<TaskStatus>),
+    Edit(TaskId, String),
+    SetRecurrence(TaskId, Option<Recurrence>),
+    SetDue(TaskId, Option<NaiveDate>),
+    SetReminder(TaskId, Option<NaiveDateTime>),
+    SetNotes(TaskId, String),
+    SetTags(TaskId, Vec<String>),
+    CountPomodoro(TaskId),
+    Import(Vec<Task>),
+    Replace(Tasks),
+    // tasks combined with the data file, written back only if they differ from it
+    Merge(Tasks),
+    Undo,
+    Redo,
+}
+
+/// apply an action to tasks, returns what changed
+pub fn reduce(tasks: &mut Tasks, action: Action) -> Vec<Change> {
+    match action {
+        Action::Add(content) => {
+            tasks.add_message(content);
+            tasks.tasks.back().map(|task| vec![Change::Add(task.clone())]).unwrap_or_default()
+        },
+        Action::SetStatus(id, status) => {
+            let previous = tasks.find_by_id(&id).map(|task| task.status.clone());
+            tasks.change_status_by_id(&id, status.clone());
+
+            let mut changes = vec![Change::Status(id.clone(), status.clone())];
+            // the status history and the tracked time changed with it
+            if let Some(task) = tasks.find_by_id(&id) {
+                changes.push(Change::Update(task.clone()));
+            }
+            let was_done = previous.map(|previous| previous == TaskStatus::Done).unwrap_or(true);
+            if status == TaskStatus::Done && !was_done {
+                if let Some(next) = tasks.repeat_by_id(&id) {
+                    changes.push(Change::Add(next));
+                }
+            }
+            changes
+        },
+        Action::Remove(id) => {
+            tasks.remove_by_id(&id);
+            vec![Change::Remove(id)]
+        },
+        Action::Clear => {
+            tasks.clear();
+            vec![Change::Clear]
+        },
+        Action::Sort(statuses) => {
+            tasks.sort(&statuses);
+            vec![Change::Sort(tasks.tasks.iter().map(|task| task.id.clone()).collect())]
+        },
+        Action::Edit(id, content) => {
+            tasks.edit_by_id(&id, content.clone());
+            vec![Change::Edit(id, content)]
+        },
+        Action::SetRecurrence(id, recurrence) => {
+            tasks.set_recurrence_by_id(&id, recurrence);
+            tasks.find_by_id(&id).map(|task| vec![Change::Update(task.clone())]).unwrap_or_default()
+        },
+        Action::SetDue(id, due) => {
+            tasks.set_due_by_id(&id, due);
+            tasks.find_by_id(&id).map(|task| vec![Change::Update(task.clone())]).unwrap_or_default()
+        },
+        Action::SetReminder(id, remind_at) => {
+            tasks.set_reminder_by_id(&id, remind_at);
+            tasks.find_by_id(&id).map(|task| vec![Change::Update(task.clone())]).unwrap_or_default()
+        },
+        Action::SetNotes(id, notes) => {
+            tasks.set_notes_by_id(&id, notes);
+            tasks.find_by_id(&id).map(|task| vec![Change::Update(task.clone())]).unwrap_or_default()
+        },
+        Action::SetTags(id, tags) => {
+            tasks.set_tags_by_id(&id, tags);
+            tasks.find_by_id(&id).map(|task| vec![Change::Update(task.clone())]).unwrap_or_default()
+        },
+        Action::CountPomodoro(id) => {
+            tasks.count_pomodoro_by_id(&id);
+            tasks.find_by_id(&id).map(|task| vec![Change::Update(task.clone())]).unwrap_or_default()
+        },
+        Action::Import(imported) => {
+            tasks.import(imported);
+            vec![Change::Replace]
+        },
+        Action::Replace(replaced) | Action::Merge(replaced) => {
+            *tasks = replaced;
+            vec![Change::Replace]
+        },
+        Action::Undo | Action::Redo => Vec::new(),
+    }
+}
+
+/// stop every running task except id
+fn pause_others(tasks: &mut Tasks, id: &TaskId) -> Vec<Change> {
+    let running: Vec<TaskId> = tasks.tasks.iter()
+        .filter(|task| !task.equals(id) && task.status == TaskStatus::Progress)
+        .map(|task| task.id.clone())
+        .collect();
+
+    running.into_iter().flat_map(|other| reduce(tasks, Action::SetStatus(other, TaskStatus::Stop))).collect()
+}
+
+impl TaskState {
+    /// apply an action to the state and schedule a save
+    pub fn dispatch(&mut self, action: Action) {
+        // a read only instance still follows the file
+        let merge = matches!(action, Action::Merge(_));
+        if (self.repository.is_read_only() && !merge) || self.repository.is_locked() {
+            return;
+        }
+
+        let changes = match action {
+            Action::Undo => match self.history.undo(&self.tasks) {
+                Some(tasks) => {
+                    self.tasks = tasks;
+                    vec![Change::Replace]
+                },
+                None => return,
+            },
+            Action::Redo => match self.history.redo(&self.tasks) {
+                Some(tasks) => {
+                    self.tasks = tasks;
+                    vec![Change::Replace]
+                },
+                None => return,
+            },
+            action => {
+                self.history.record(&self.tasks);
+
+                let mut changes = Vec::new();
+                if let Action::SetStatus(id, TaskStatus::Progress) = &action {
+                    if self.single_running {
+                        changes.append(&mut pause_others(&mut self.tasks, id));
+                    }
+                }
+                changes.append(&mut reduce(&mut self.tasks, action));
+                changes
+            },
+        };
+
+        let tasks = self.tasks.to_save_tasks();
+        if merge && self.repository.is_synced(&tasks) {
+            return;
+        }
+        self.autosave.apply(changes, tasks);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::SaveTasks;
+
+    fn tasks_with(contents: &[&str]) -> Tasks {
+        let mut tasks = Tasks::from_save_tasks(SaveTasks::new());
+        for content in contents.iter() {
+            reduce(&mut tasks, Action::Add(content.to_string()));
+        }
+        tasks
+    }
+
+    fn first_id(tasks: &Tasks) -> TaskId {
+        tasks.tasks[0].id.clone()
+    }
+
+    #[test]
+    fn add_appends_a_new_task() {
+        let mut tasks = tasks_with(&["first"]);
+        let changes = reduce(&mut tasks, Action::Add("second".to_string()));
+
+        assert_eq!(tasks.tasks.len(), 2);
+        assert_eq!(tasks.tasks[1].content, "second");
+        assert_eq!(tasks.tasks[1].status, TaskStatus::New);
+        assert!(matches!(changes.as_slice(), [Change::Add(task)] if task.content == "second"));
+    }
+
+    #[test]
+    fn edit_and_remove() {
+        let mut tasks = tasks_with(&["first", "second"]);
+        let id = first_id(&tasks);
+
+        let changes = reduce(&mut tasks, Action::Edit(id.clone(), "edited".to_string()));
+        assert_eq!(tasks.tasks[0].content, "edited");
+        assert!(matches!(changes.as_slice(), [Change::Edit(_, content)] if content == "edited"));
+
+        let changes = reduce(&mut tasks, Action::Remove(id.clone()));
+        assert_eq!(tasks.tasks.len(), 1);
+        assert!(!tasks.tasks[0].equals(&id));
+        assert!(matches!(changes.as_slice(), [Change::Remove(_)]));
+    }
+
+    #[test]
+    fn progress_starts_tracking() {
+        let mut tasks = tasks_with(&["task"]);
+        let id = first_id(&tasks);
+
+        let changes = reduce(&mut tasks, Action::SetStatus(id.clone(), TaskStatus::Progress));
+        assert!(tasks.tasks[0].is_running());
+        assert!(matches!(changes.as_slice(), [Change::Status(_, _), Change::Update(_)]));
+
+        reduce(&mut tasks, Action::SetStatus(id, TaskStatus::Stop));
+        assert!(!tasks.tasks[0].is_running());
+        assert_eq!(tasks.tasks[0].intervals.len(), 1);
+    }
+
+    #[test]
+    fn completing_a_recurring_task_adds_the_next_one() {
+        let mut tasks = tasks_with(&["daily"]);
+        let id = first_id(&tasks);
+        reduce(&mut tasks, Action::SetRecurrence(id.clone(), Some(Recurrence::Daily)));
+
+        let changes = reduce(&mut tasks, Action::SetStatus(id, TaskStatus::Done));
+        assert_eq!(tasks.tasks.len(), 2);
+        assert_eq!(tasks.tasks[1].status, TaskStatus::New);
+        assert!(matches!(changes.last(), Some(Change::Add(_))));
+    }
+
+    #[test]
+    fn notes_and_tags_update_the_task() {
+        let mut tasks = tasks_with(&["task"]);
+        let id = first_id(&tasks);
+
+        reduce(&mut tasks, Action::SetNotes(id.clone(), "**notes**".to_string()));
+        let changes = reduce(&mut tasks, Action::SetTags(id, vec!["work".to_string()]));
+        assert_eq!(tasks.tasks[0].notes, "**notes**");
+        assert_eq!(tasks.tasks[0].tags, vec!["work".to_string()]);
+        assert!(matches!(changes.as_slice(), [Change::Update(_)]));
+    }
+
+    #[test]
+    fn merge_takes_the_combined_tasks() {
+        let mut tasks = tasks_with(&["mine"]);
+        let changes = reduce(&mut tasks, Action::Merge(tasks_with(&["theirs"])));
+        assert_eq!(tasks.tasks[0].content, "theirs");
+        assert!(matches!(changes.as_slice(), [Change::Replace]));
+    }
+}
@@ -0,0 +1,344 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use druid::{ExtEventSink, Selector, Target};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::io::Read;
+use std::net::Ipv4Addr;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::action::Action;
+use crate::model::{Task, TaskId, TaskStatus};
+use crate::TaskState;
+
+/// api request to be answered on the ui thread
+pub const API_CALL: Selector<ApiCall> = Selector::new("tasking.api.call");
+
+// the ui thread may be busy with a dialog
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// request from an api client
+#[derive(Debug, Clone)]
+pub enum ApiRequest {
+    List,
+    Get(TaskId),
+    Add(TaskPatch),
+    Update(TaskId, TaskPatch),
+    Delete(TaskId),
+}
+
+/// fields sent by a client, missing ones are left as is
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct TaskPatch {
+    pub content: Option<String>,
+    pub status: Option<TaskStatus>,
+    pub due: Option<NaiveDate>,
+    pub remind_at: Option<NaiveDateTime>,
+}
+
+/// status code and json body
+#[derive(Debug, Clone)]
+pub struct ApiReply {
+    pub status: u16,
+    pub body: String,
+}
+
+impl ApiReply {
+    fn json<T: Serialize>(status: u16, body: &T) -> Self {
+        Self {
+            status: status,
+            body: serde_json::to_string(body).expect("シリアライズできませんでした"),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::json(status, &json!({ "error": message }))
+    }
+}
+
+/// request and where to send the reply
+#[derive(Clone)]
+pub struct ApiCall {
+    pub request: ApiRequest,
+    reply: Arc<Mutex<Sender<ApiReply>>>,
+}
+
+impl ApiCall {
+    /// answer the client
+    pub fn reply(&self, reply: ApiReply) {
+        let _ = self.reply.lock().expect("返信先を取得できませんでした").send(reply);
+    }
+}
+
+/// serve the api on localhost in the background
+pub fn serve(port: u16, sink: ExtEventSink) -> Result<(), String> {
+    let server = Server::http((Ipv4Addr::LOCALHOST, port)).map_err(|e| e.to_string())?;
+
+    thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let reply = match parse(&mut request, port) {
+                Ok(api_request) => call(&sink, api_request),
+                Err(reply) => reply,
+            };
+
+            let header = Header::from_bytes("Content-Type", "application/json; charset=utf-8").expect("ヘッダーを作成できませんでした");
+            let response = Response::from_string(reply.body)
+                .with_status_code(reply.status)
+                .with_header(header);
+            let _ = request.respond(response);
+        }
+    });
+
+    Ok(())
+}
+
+/// hand the request to the ui thread and wait for the reply
+fn call(sink: &ExtEventSink, request: ApiRequest) -> ApiReply {
+    let (sender, receiver) = mpsc::channel();
+    let call = ApiCall {
+        request: request,
+        reply: Arc::new(Mutex::new(sender)),
+    };
+
+    if sink.submit_command(API_CALL, call, Target::Auto).is_err() {
+        return ApiReply::error(503, "アプリが終了しています")
+    }
+    receiver.recv_timeout(REPLY_TIMEOUT).unwrap_or_else(|_| ApiReply::error(503, "アプリが応答しません"))
+}
+
+fn parse(request: &mut Request, port: u16) -> Result<ApiRequest, ApiReply> {
+    // pages rebound to 127.0.0.1 by dns still send their own host name
+    let host = request.headers().iter().find(|header| header.field.equiv("Host")).map(|header| header.value.as_str());
+    if !host.map(|host| is_local_host(host, port)).unwrap_or(false) {
+        return Err(ApiReply::error(403, "Host が正しくありません"))
+    }
+
+    let path = request.url().split('?').next().unwrap_or("").trim_matches('/').to_string();
+    let segments: Vec<&str> = path.split('/').collect();
+
+    let method = request.method().clone();
+
+    match (method, segments.as_slice()) {
+        (Method::Get, ["tasks"]) => Ok(ApiRequest::List),
+        (Method::Get, ["tasks", id]) => Ok(ApiRequest::Get(TaskId::from(*id))),
+        (Method::Post, ["tasks"]) => {
+            let patch = read_patch(request)?;
+            match &patch.content {
+                Some(content) if !content.trim().is_empty() => Ok(ApiRequest::Add(patch)),
+                _ => Err(ApiReply::error(400, "content が必要です")),
+            }
+        },
+        (Method::Patch, ["tasks", id]) => {
+            let id = TaskId::from(*id);
+            Ok(ApiRequest::Update(id, read_patch(request)?))
+        },
+        (Method::Delete, ["tasks", id]) => Ok(ApiRequest::Delete(TaskId::from(*id))),
+        (_, ["tasks"]) | (_, ["tasks", _]) => Err(ApiReply::error(405, "このメソッドは使えません")),
+        _ => Err(ApiReply::error(404, "見つかりません")),
+    }
+}
+
+/// host header naming this server
+fn is_local_host(host: &str, port: u16) -> bool {
+    host == format!("127.0.0.1:{}", port) || host == format!("localhost:{}", port)
+}
+
+fn read_patch(request: &mut Request) -> Result<TaskPatch, ApiReply> {
+    // browsers cannot send json across sites without a preflight we never answer
+    let is_json = request.headers().iter().any(|header| {
+        header.field.equiv("Content-Type") && header.value.as_str().starts_with("application/json")
+    });
+    if !is_json {
+        return Err(ApiReply::error(415, "Content-Type は application/json にしてください"))
+    }
+
+    let mut body = String::new();
+    request.as_reader().read_to_string(&mut body).map_err(|_| ApiReply::error(400, "本文を読み込めませんでした"))?;
+    serde_json::from_str(&body).map_err(|e| ApiReply::error(400, &e.to_string()))
+}
+
+impl TaskState {
+    /// answer an api request, changes go through dispatch like the ui
+    pub fn answer(&mut self, request: ApiRequest) -> ApiReply {
+        let writes = match request {
+            ApiRequest::List | ApiRequest::Get(_) => false,
+            _ => true,
+        };
+        if writes && (self.repository.is_read_only() || self.repository.is_locked()) {
+            return ApiReply::error(423, "データファイルに書き込めません")
+        }
+
+        match request {
+            ApiRequest::List => ApiReply::json(200, &self.tasks.to_vec()),
+            ApiRequest::Get(id) => match self.find_task(&id) {
+                Some(task) => ApiReply::json(200, &task),
+                None => ApiReply::error(404, "タスクが見つかりません"),
+            },
+            ApiRequest::Add(patch) => {
+                self.dispatch(Action::Add(patch.content.unwrap_or_default()));
+                let id = match self.tasks.tasks.back() {
+                    Some(task) => task.id.clone(),
+                    None => return ApiReply::error(500, "タスクを追加できませんでした"),
+                };
+                self.apply_patch(&id, TaskPatch {
+                    content: None,
+                    ..patch
+                });
+                self.reply_task(201, &id)
+            },
+            ApiRequest::Update(id, patch) => {
+                if self.find_task(&id).is_none() {
+                    return ApiReply::error(404, "タスクが見つかりません")
+                }
+                self.apply_patch(&id, patch);
+                self.reply_task(200, &id)
+            },
+            ApiRequest::Delete(id) => {
+                if self.find_task(&id).is_none() {
+                    return ApiReply::error(404, "タスクが見つかりません")
+                }
+                self.dispatch(Action::Remove(id));
+                ApiReply {
+                    status: 204,
+                    body: String::new(),
+                }
+            },
+        }
+    }
+
+    fn apply_patch(&mut self, id: &TaskId, patch: TaskPatch) {
+        if let Some(content) = patch.content {
+            self.dispatch(Action::Edit(id.clone(), content));
+        }
+        if let Some(status) = patch.status {
+            self.dispatch(Action::SetStatus(id.clone(), status));
+        }
+        if let Some(due) = patch.due {
+            self.dispatch(Action::SetDue(id.clone(), Some(due)));
+        }
+        if let Some(remind_at) = patch.remind_at {
+            self.dispatch(Action::SetReminder(id.clone(), Some(remind_at)));
+        }
+    }
+
+    fn find_task(&self, id: &TaskId) -> Option<Task> {
+        self.tasks.tasks.iter().find(|task| task.equals(id)).cloned()
+    }
+
+    fn reply_task(&self, status: u16, id: &TaskId) -> ApiReply {
+        match self.find_task(id) {
+            Some(task) => ApiReply::json(status, &task),
+            None => ApiReply::error(404, "タスクが見つかりません"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use super::*;
+    use crate::config::{Config, Storage};
+    use crate::model::{SaveTasks, TaskRepository};
+    use crate::store::EncryptedStore;
+
+    fn data_path(name: &str) -> String {
+        env::temp_dir().join(format!("tasking-{}-{}.json", name, TaskId::generate())).to_string_lossy().to_string()
+    }
+
+    fn state(path: &str) -> TaskState {
+        let repository = TaskRepository::open(path.to_string(), Storage::Json).expect("repository").keep_backups(0);
+        TaskState::new(repository, &Config::default())
+    }
+
+    fn patch(content: Option<&str>, status: Option<TaskStatus>) -> TaskPatch {
+        TaskPatch {
+            content: content.map(|content| content.to_string()),
+            status: status,
+            ..TaskPatch::default()
+        }
+    }
+
+    #[test]
+    fn accepts_only_local_hosts_with_the_port() {
+        assert!(is_local_host("127.0.0.1:7878", 7878));
+        assert!(is_local_host("localhost:7878", 7878));
+        assert!(!is_local_host("127.0.0.1:8080", 7878));
+        assert!(!is_local_host("127.0.0.1", 7878));
+        assert!(!is_local_host("evil.example.com:7878", 7878));
+    }
+
+    #[test]
+    fn post_adds_a_task_with_its_status() {
+        let path = data_path("api-post");
+        let mut state = state(&path);
+
+        let reply = state.answer(ApiRequest::Add(patch(Some("task"), Some(TaskStatus::Progress))));
+        assert_eq!(reply.status, 201);
+        let task: Task = serde_json::from_str(&reply.body).expect("task");
+        assert_eq!(task.content, "task");
+        assert_eq!(task.status, TaskStatus::Progress);
+        assert_eq!(state.tasks.tasks.len(), 1);
+        assert_eq!(state.tasks.tasks[0], task);
+
+        state.autosave.flush();
+        assert_eq!(state.repository.load().tasks, vec![task]);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn patch_and_delete_need_an_existing_task() {
+        let path = data_path("api-missing");
+        let mut state = state(&path);
+
+        let missing = TaskId::generate();
+        assert_eq!(state.answer(ApiRequest::Update(missing.clone(), patch(Some("edit"), None))).status, 404);
+        assert_eq!(state.answer(ApiRequest::Delete(missing.clone())).status, 404);
+        assert_eq!(state.answer(ApiRequest::Get(missing)).status, 404);
+        assert!(state.tasks.tasks.is_empty());
+
+        state.autosave.flush();
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn patch_edits_and_delete_removes() {
+        let path = data_path("api-delete");
+        let mut state = state(&path);
+        state.dispatch(Action::Add("task".to_string()));
+        let id = state.tasks.tasks[0].id.clone();
+
+        let reply = state.answer(ApiRequest::Update(id.clone(), patch(Some("edited"), Some(TaskStatus::Done))));
+        assert_eq!(reply.status, 200);
+        assert_eq!(state.tasks.tasks[0].content, "edited");
+        assert_eq!(state.tasks.tasks[0].status, TaskStatus::Done);
+
+        let reply = state.answer(ApiRequest::Delete(id.clone()));
+        assert_eq!(reply.status, 204);
+        assert!(reply.body.is_empty());
+        assert!(state.tasks.tasks.is_empty());
+
+        state.autosave.flush();
+        assert!(state.repository.load().tasks.is_empty());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn locked_file_refuses_writes() {
+        let path = data_path("api-locked");
+        EncryptedStore::create(&path, "passphrase", &SaveTasks::new());
+        let mut state = state(&path);
+        assert!(state.repository.is_locked());
+
+        assert_eq!(state.answer(ApiRequest::Add(patch(Some("task"), None))).status, 423);
+        assert_eq!(state.answer(ApiRequest::Delete(TaskId::generate())).status, 423);
+        assert_eq!(state.answer(ApiRequest::List).status, 200);
+        assert!(state.tasks.tasks.is_empty());
+
+        fs::remove_file(&path).expect("remove");
+    }
+}
@@ -0,0 +1,133 @@
+use druid::Data;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::model::{SaveTasks, TaskRepository};
+use crate::store::Change;
+
+enum Message {
+    Save(Vec<Change>, SaveTasks),
+    Flush(Sender<()>),
+}
+
+/// debounced background saver
+#[derive(Clone, Data)]
+pub struct Autosave {
+    sender: Arc<Mutex<Sender<Message>>>,
+    // written directly when the save thread is gone
+    repository: TaskRepository,
+}
+
+impl Autosave {
+    /// construct and start the save thread
+    pub fn new(repository: TaskRepository, delay: Duration) -> Self {
+        let (sender, receiver) = mpsc::channel::<Message>();
+
+        let writer = repository.clone();
+        thread::spawn(move || {
+            // changes not yet written and the latest tasks
+            let mut pending: Option<(Vec<Change>, SaveTasks)> = None;
+
+            loop {
+                let message = match pending {
+                    Some(_) => receiver.recv_timeout(delay),
+                    None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+
+                match message {
+                    Ok(Message::Save(mut changes, tasks)) => {
+                        if let Some((mut previous, _)) = pending.take() {
+                            previous.append(&mut changes);
+                            changes = previous;
+                        }
+                        pending = Some((changes, tasks));
+                    },
+                    Ok(Message::Flush(done)) => {
+                        if let Some((changes, tasks)) = pending.take() {
+                            writer.apply(&changes, tasks);
+                        }
+                        let _ = done.send(());
+                    },
+                    Err(RecvTimeoutError::Timeout) => {
+                        if let Some((changes, tasks)) = pending.take() {
+                            writer.apply(&changes, tasks);
+                        }
+                    },
+                    Err(RecvTimeoutError::Disconnected) => {
+                        if let Some((changes, tasks)) = pending.take() {
+                            writer.apply(&changes, tasks);
+                        }
+                        break;
+                    },
+                }
+            }
+        });
+
+        Self {
+            sender: Arc::new(Mutex::new(sender)),
+            repository: repository,
+        }
+    }
+
+    /// schedule tasks to be saved as a whole after the debounce delay
+    pub fn save(&self, tasks: SaveTasks) {
+        self.apply(vec![Change::Replace], tasks);
+    }
+
+    /// schedule changes to be saved after the debounce delay
+    pub fn apply(&self, changes: Vec<Change>, tasks: SaveTasks) {
+        let sender = self.sender.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(mpsc::SendError(Message::Save(changes, tasks))) = sender.send(Message::Save(changes, tasks)) {
+            eprintln!("自動保存を利用できませんでした、すぐに保存します");
+            self.repository.apply(&changes, tasks);
+        }
+    }
+
+    /// write pending tasks now and wait for completion
+    pub fn flush(&self) {
+        let (done, wait) = mpsc::channel();
+        {
+            let sender = self.sender.lock().unwrap_or_else(|e| e.into_inner());
+            if sender.send(Message::Flush(done)).is_err() {
+                return;
+            }
+        }
+        let _ = wait.recv();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use super::*;
+    use crate::config::Storage;
+    use crate::model::{Task, TaskId};
+    use crate::store::{JsonStore, TaskStore};
+
+    #[test]
+    fn saves_directly_after_the_save_thread_died() {
+        let dir = env::temp_dir().join(format!("tasking-autosave-{}", TaskId::generate()));
+        let path = dir.join("task.json").to_string_lossy().to_string();
+        let repository = TaskRepository::open(path.clone(), Storage::Json).expect("repository").keep_backups(0);
+        let autosave = Autosave::new(repository, Duration::ZERO);
+
+        // the folder is missing, the writer panics
+        autosave.save(SaveTasks::new());
+        autosave.flush();
+        // until the thread has unwound and dropped its receiver
+        thread::sleep(Duration::from_millis(200));
+
+        fs::create_dir_all(&dir).expect("folder");
+        let tasks = SaveTasks {
+            tasks: vec![Task::create(TaskId::generate(), "task".to_string())],
+        };
+        autosave.save(tasks.clone());
+        assert_eq!(JsonStore::new(&path).load().expect("tasks").tasks, tasks.tasks);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
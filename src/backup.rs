@@ -0,0 +1,186 @@
+use chrono::{Local, NaiveDate};
+use druid::{Data, Lens, Selector};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::{tr, tr_args};
+use crate::model::{SaveTasks, Task, TaskId};
+
+/// restore tasks from the backup file at path
+pub const RESTORE_BACKUP: Selector<String> = Selector::new("tasking.backup.restore");
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+// diff lines shown per backup
+const DIFF_LINES: usize = 5;
+
+/// dated backups of the data file
+#[derive(Debug, Clone)]
+pub struct Backups {
+    dir: PathBuf,
+    stem: String,
+    keep: usize,
+}
+
+/// a single backup file
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub date: NaiveDate,
+    pub path: PathBuf,
+}
+
+/// backup row for the restore view
+#[derive(Clone, Data, Lens)]
+pub struct BackupItem {
+    pub date: String,
+    pub path: String,
+    pub count: usize,
+    pub diff: String,
+}
+
+impl Backups {
+    /// backups folder next to the data file
+    pub fn new(filename: &str, keep: usize) -> Self {
+        let path = Path::new(filename);
+        let dir = path.parent().unwrap_or(Path::new(".")).join("backups");
+        let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("task").to_string();
+
+        Self {
+            dir: dir,
+            stem: stem,
+            keep: keep,
+        }
+    }
+
+    /// number of backups kept
+    pub fn keep(&self) -> usize {
+        self.keep
+    }
+
+    /// today's backup has been written
+    pub fn has_today(&self) -> bool {
+        self.keep == 0 || self.path_for(Local::now().date_naive()).exists()
+    }
+
+    /// write today's backup and drop the oldest ones
+    pub fn write_today(&self, serialized: &str) {
+        if self.keep == 0 {
+            return
+        }
+
+        // a missing backup must not stop the save
+        if let Err(e) = fs::create_dir_all(&self.dir) {
+            eprintln!("{} フォルダを作成できませんでした: {}", self.dir.display(), e);
+            return
+        }
+        let path = self.path_for(Local::now().date_naive());
+        if let Err(e) = fs::write(&path, serialized) {
+            eprintln!("{} ファイルに書き込めませんでした: {}", path.display(), e);
+            return
+        }
+
+        for backup in self.list().into_iter().skip(self.keep) {
+            let _ = fs::remove_file(backup.path);
+        }
+    }
+
+    /// backups, newest first
+    pub fn list(&self) -> Vec<Backup> {
+        let prefix = format!("{}-", self.stem);
+        let mut backups: Vec<Backup> = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let path = entry.path();
+                    let name = path.file_stem()?.to_str()?;
+                    let date = NaiveDate::parse_from_str(name.strip_prefix(prefix.as_str())?, DATE_FORMAT).ok()?;
+                    Some(Backup {
+                        date: date,
+                        path: path,
+                    })
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        backups.sort_by(|a, b| b.date.cmp(&a.date));
+
+        backups
+    }
+
+    /// rows comparing each backup with the current tasks
+    pub fn items(&self, current: &SaveTasks, decode: impl Fn(&str) -> Option<SaveTasks>) -> Vec<BackupItem> {
+        self.list()
+            .into_iter()
+            .filter_map(|backup| {
+                let tasks = decode(&fs::read_to_string(&backup.path).ok()?)?;
+                Some(BackupItem {
+                    date: backup.date.format(DATE_FORMAT).to_string(),
+                    path: backup.path.display().to_string(),
+                    count: tasks.tasks.len(),
+                    diff: Diff::between(current, &tasks).to_string(),
+                })
+            })
+            .collect()
+    }
+
+    fn path_for(&self, date: NaiveDate) -> PathBuf {
+        self.dir.join(format!("{}-{}.json", self.stem, date.format(DATE_FORMAT)))
+    }
+}
+
+/// what restoring a backup would change
+pub struct Diff {
+    // only in the backup, would come back
+    pub restored: Vec<Task>,
+    // only in the current tasks, would be lost
+    pub lost: Vec<Task>,
+    // in both with different content or status
+    pub changed: Vec<Task>,
+}
+
+impl Diff {
+    /// compare current tasks with a backup
+    pub fn between(current: &SaveTasks, backup: &SaveTasks) -> Self {
+        let find = |tasks: &SaveTasks, id: &TaskId| tasks.tasks.iter().find(|task| task.equals(id)).cloned();
+
+        Self {
+            restored: backup.tasks.iter().filter(|task| find(current, &task.id).is_none()).cloned().collect(),
+            lost: current.tasks.iter().filter(|task| find(backup, &task.id).is_none()).cloned().collect(),
+            changed: backup.tasks
+                .iter()
+                .filter(|task| match find(current, &task.id) {
+                    Some(c) => c.content != task.content || c.status != task.status,
+                    None => false,
+                })
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.restored.is_empty() && self.lost.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl std::fmt::Display for Diff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "{}", tr("backup-same"))
+        }
+
+        write!(f, "{}", tr_args("backup-diff", &[
+            ("restored", &self.restored.len()),
+            ("lost", &self.lost.len()),
+            ("changed", &self.changed.len()),
+        ]))?;
+
+        let lines = self.restored.iter().map(|task| format!("+ {}", task.content))
+            .chain(self.lost.iter().map(|task| format!("- {}", task.content)))
+            .chain(self.changed.iter().map(|task| format!("~ {}", task.content)))
+            .take(DIFF_LINES);
+        for line in lines {
+            write!(f, "\n{}", line)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use druid::im::Vector;
+use druid::widget::prelude::*;
+use druid::widget::{Controller, Flex, Label, LineBreaking, List, Painter, Scroll};
+use druid::{lens, LensExt, WidgetExt};
+
+use crate::action::Action;
+use crate::model::{Task, TaskStatus};
+use crate::settings::TASK_TEXT_SIZE;
+use crate::theme::{self, status_color};
+use crate::{TaskState, BLOCK_SPACE, BORDER_RADIUS};
+
+/// picks up a card on mouse down
+struct DragSource;
+
+impl<W: Widget<(TaskState, Task)>> Controller<(TaskState, Task), W> for DragSource {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut (TaskState, Task), env: &Env) {
+        if let Event::MouseDown(_) = event {
+            let (state, task) = data;
+            state.dragging = Some(task.id.clone());
+        }
+
+        child.event(ctx, event, data, env)
+    }
+}
+
+/// column that moves a dropped card to its status
+struct DropTarget {
+    status: TaskStatus,
+}
+
+impl<W: Widget<TaskState>> Controller<TaskState, W> for DropTarget {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut TaskState, env: &Env) {
+        if let Event::MouseUp(_) = event {
+            if let Some(id) = data.dragging.take() {
+                let moved = data.tasks.tasks.iter().any(|task| task.equals(&id) && task.status != self.status);
+                if moved {
+                    data.dispatch(Action::SetStatus(id, self.status.clone()));
+                }
+            }
+        }
+
+        child.event(ctx, event, data, env)
+    }
+
+    fn lifecycle(&mut self, child: &mut W, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &TaskState, env: &Env) {
+        if let LifeCycle::HotChanged(_) = event {
+            ctx.request_paint();
+        }
+
+        child.lifecycle(ctx, event, data, env)
+    }
+}
+
+/// drops outside of a column put the card back
+struct DragEnd;
+
+impl<W: Widget<TaskState>> Controller<TaskState, W> for DragEnd {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut TaskState, env: &Env) {
+        child.event(ctx, event, data, env);
+
+        if let Event::MouseUp(_) = event {
+            data.dragging = None;
+        }
+    }
+}
+
+fn make_card() -> impl Widget<(TaskState, Task)> {
+    let painter = Painter::new(|ctx, (state, task): &(TaskState, Task), env| {
+        let bounds = ctx.size().to_rounded_rect(BORDER_RADIUS);
+        let color = env.get(status_color(&task.status));
+
+        let alpha = match state.dragging.as_ref() == Some(&task.id) {
+            true => 0.47,
+            false => 1.0,
+        };
+        ctx.fill(bounds, &env.get(theme::SURFACE).with_alpha(alpha));
+        ctx.stroke(bounds.rect().inset(-0.5).to_rounded_rect(BORDER_RADIUS), &color.with_alpha(alpha), 1.0);
+    });
+
+    Label::new(|(_, task): &(TaskState, Task), _: &Env| task.content.to_string())
+        .with_text_size(TASK_TEXT_SIZE)
+        .with_line_break_mode(LineBreaking::WordWrap)
+        .padding(5.0)
+        .expand_width()
+        .background(painter)
+        .controller(DragSource)
+}
+
+fn make_column(status: TaskStatus) -> impl Widget<TaskState> {
+    let color = status_color(&status);
+    let painter = Painter::new(move |ctx, data: &TaskState, env| {
+        let bounds = ctx.size().to_rounded_rect(BORDER_RADIUS);
+        let alpha = match data.dragging.is_some() && ctx.is_hot() {
+            true => 0.24,
+            false => 0.08,
+        };
+        ctx.fill(bounds, &env.get(&color).with_alpha(alpha));
+    });
+
+    let filter = Some(status.clone());
+    let heading = status.clone();
+
+    Flex::column()
+        .with_child(
+            Label::new(move |data: &TaskState, _: &Env| {
+                let count = data.tasks.tasks.iter().filter(|task| task.status == heading).count();
+                format!("{} {}", heading.to_string(), count)
+            })
+                .with_text_size(TASK_TEXT_SIZE)
+                .with_text_color(status_color(&status))
+                .padding(5.0),
+        )
+        .with_flex_child(
+            Scroll::new(
+                List::new(make_card)
+                    .with_spacing(BLOCK_SPACE / 2.0)
+                    .lens(lens::Identity.map(
+                        move |d: &TaskState| (d.clone(), d.tasks.filter(&filter)),
+                        |d: &mut TaskState, (state, _): (TaskState, Vector<Task>)| {
+                            d.dragging = state.dragging;
+                        },
+                    )),
+            )
+                .vertical()
+                .expand(),
+            1.0,
+        )
+        .padding(5.0)
+        .background(painter)
+        .controller(DropTarget {
+            status: status,
+        })
+}
+
+/// one column per status, cards are dragged between them
+pub fn make_board_view() -> impl Widget<TaskState> {
+    let mut board = Flex::row();
+    for (index, status) in TaskStatus::statuses().into_iter().enumerate() {
+        if index > 0 {
+            board.add_spacer(BLOCK_SPACE);
+        }
+        board.add_flex_child(make_column(status), 1.0);
+    }
+
+    board
+        .expand()
+        .padding(BLOCK_SPACE)
+        .controller(DragEnd)
+}
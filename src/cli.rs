@@ -0,0 +1,157 @@
+use std::path::Path;
+use std::process;
+
+use crate::config::{app_path, Storage};
+use crate::merge;
+use crate::model::SaveTasks;
+use crate::store::{self, EncryptedStore, JournalStore, JsonStore, SqliteStore, TaskStore};
+
+/// run a command line subcommand, returns false to start the app
+pub fn run(args: &[String]) -> bool {
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("migrate") => {
+            let json = args.get(2).cloned().unwrap_or_else(|| app_path(Storage::Json.default_filename()));
+            let db = args.get(3).cloned().unwrap_or_else(|| app_path(Storage::Sqlite.default_filename()));
+
+            match migrate(&json, &db) {
+                true => println!("{} を {} に移行しました", json, db),
+                false => eprintln!("{} を移行できませんでした（データベースが空ではないか、ファイルを読み込めません）", json),
+            }
+            true
+        },
+        Some("log") => {
+            let journal = args.get(2).cloned().unwrap_or_else(|| app_path(Storage::Journal.default_filename()));
+            for entry in JournalStore::new(&journal).history().iter() {
+                println!("{}\t{}\t{}", entry.seq, entry.at, entry.change);
+            }
+            true
+        },
+        Some("encrypt") => {
+            let file = args.get(2).cloned().unwrap_or_else(|| app_path(Storage::Json.default_filename()));
+            match EncryptedStore::is_encrypted(&file) {
+                true => eprintln!("{} はすでに暗号化されています", file),
+                false => {
+                    if Storage::from_filename(&file).unwrap_or(Storage::Json) != Storage::Json {
+                        eprintln!("{} は JSON のデータファイルではないため暗号化できません", file);
+                        process::exit(1);
+                    }
+                    // a file that cannot be read is never overwritten
+                    let tasks = match JsonStore::new(&file).load() {
+                        Some(tasks) => tasks,
+                        None if Path::new(&file).exists() => {
+                            eprintln!("{} を読み込めないため暗号化できません", file);
+                            process::exit(1);
+                        },
+                        None => SaveTasks::new(),
+                    };
+                    if let Some(passphrase) = prompt_new_passphrase() {
+                        EncryptedStore::create(&file, &passphrase, &tasks);
+                        println!("{} を暗号化しました", file);
+                    }
+                },
+            }
+            true
+        },
+        Some("decrypt") => {
+            let file = args.get(2).cloned().unwrap_or_else(|| app_path(Storage::Json.default_filename()));
+            match decrypt(&file) {
+                Some(tasks) => {
+                    JsonStore::new(&file).save(&tasks);
+                    println!("{} の暗号化を解除しました", file);
+                },
+                None => eprintln!("{} を復号できませんでした", file),
+            }
+            true
+        },
+        Some("passwd") => {
+            let file = args.get(2).cloned().unwrap_or_else(|| app_path(Storage::Json.default_filename()));
+            match decrypt(&file) {
+                Some(tasks) => {
+                    if let Some(passphrase) = prompt_new_passphrase() {
+                        EncryptedStore::create(&file, &passphrase, &tasks);
+                        println!("{} のパスフレーズを変更しました", file);
+                    }
+                },
+                None => eprintln!("{} を復号できませんでした", file),
+            }
+            true
+        },
+        Some("merge") => {
+            let (base, local, remote) = match (args.get(2), args.get(3), args.get(4)) {
+                (Some(base), Some(local), Some(remote)) => (base, local, remote),
+                _ => {
+                    eprintln!("使い方: merge <base> <local> <remote> [output]");
+                    process::exit(2);
+                },
+            };
+            // writes over local by default, like a git merge driver
+            let output = args.get(5).unwrap_or(local);
+
+            let load = |file: &str| store::open_file(file)?.load().ok_or(format!("{} を読み込めませんでした", file));
+            let merged = match (load(base), load(local), load(remote)) {
+                (Ok(base), Ok(local), Ok(remote)) => merge::merge(&base, &local, &remote),
+                (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                    eprintln!("{}", e);
+                    process::exit(2);
+                },
+            };
+
+            match store::open_file(output) {
+                Ok(mut store) => store.save(&merged.tasks),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(2);
+                },
+            }
+            if !merged.is_clean() {
+                for conflict in merged.conflicts.iter() {
+                    eprintln!("競合: {}", conflict);
+                }
+                process::exit(1);
+            }
+            true
+        },
+        _ => false,
+    }
+}
+
+/// ask for the current passphrase and decrypt file
+fn decrypt(file: &str) -> Option<SaveTasks> {
+    let passphrase = rpassword::prompt_password("パスフレーズ: ").ok()?;
+    EncryptedStore::decrypt(file, &passphrase)
+}
+
+/// ask for a new passphrase twice
+fn prompt_new_passphrase() -> Option<String> {
+    let passphrase = rpassword::prompt_password("新しいパスフレーズ: ").ok()?;
+    let confirm = rpassword::prompt_password("もう一度入力してください: ").ok()?;
+
+    if passphrase.is_empty() || passphrase != confirm {
+        eprintln!("パスフレーズが一致しません");
+        return None
+    }
+
+    Some(passphrase)
+}
+
+/// copy json tasks into an empty sqlite database
+pub fn migrate(json: &str, db: &str) -> bool {
+    match SqliteStore::open(db) {
+        Ok(mut store) => store.migrate_from_json(json),
+        Err(e) => {
+            eprintln!("{}", e);
+            false
+        },
+    }
+}
+
+/// migrate on the first use of the database only
+pub fn migrate_once(json: &str, db: &str) -> bool {
+    match SqliteStore::open(db) {
+        Ok(mut store) => store.migrate_from_json_once(json),
+        Err(e) => {
+            eprintln!("{}", e);
+            false
+        },
+    }
+}
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use crate::i18n::tr_args;
+use crate::settings::Settings;
+
+// config json file next to the executable
+const CONFIG_FILENAME: &str = "config.json";
+
+/// storage backend
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Storage {
+    Json,
+    Sqlite,
+    Journal,
+}
+
+impl Storage {
+    /// default data file for the backend
+    pub fn default_filename(&self) -> &str {
+        match self {
+            Self::Json => "task.json",
+            Self::Sqlite => "task.db",
+            Self::Journal => "task.jsonl",
+        }
+    }
+
+    /// backend the extension of a data file stands for
+    pub fn from_filename(filename: &str) -> Option<Self> {
+        match Path::new(filename).extension().and_then(|ext| ext.to_str()) {
+            Some("db") | Some("sqlite") | Some("sqlite3") => Some(Self::Sqlite),
+            Some("jsonl") => Some(Self::Journal),
+            Some("json") => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// git sync of the data directory
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SyncConfig {
+    pub remote: String,
+    #[serde(default = "default_branch")]
+    pub branch: String,
+}
+
+fn default_branch() -> String {
+    "main".to_string()
+}
+
+/// local http api
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiConfig {
+    #[serde(default = "default_port")]
+    pub port: u16,
+}
+
+fn default_port() -> u16 {
+    7878
+}
+
+/// pomodoro phase lengths in minutes
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PomodoroConfig {
+    pub work_minutes: i64,
+    pub break_minutes: i64,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        Self {
+            work_minutes: 25,
+            break_minutes: 5,
+        }
+    }
+}
+
+/// main window size and position
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct WindowGeometry {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// application config
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Config {
+    pub storage: Storage,
+    // dated backups to keep, 0 disables them
+    pub backups: usize,
+    pub sync: Option<SyncConfig>,
+    pub api: Option<ApiConfig>,
+    // starting a task stops the running one
+    pub single_running: bool,
+    pub pomodoro: PomodoroConfig,
+    // written by the app when the window closes
+    pub window: Option<WindowGeometry>,
+    // edited on the settings screen
+    #[serde(flatten)]
+    pub settings: Settings,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            storage: Storage::Json,
+            backups: 7,
+            sync: None,
+            api: None,
+            single_running: false,
+            pomodoro: PomodoroConfig::default(),
+            window: None,
+            settings: Settings::default(),
+        }
+    }
+}
+
+impl Config {
+    /// load config, defaults if missing or broken
+    pub fn load() -> Self {
+        match Self::try_load() {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("{}", e);
+                Self::default()
+            },
+        }
+    }
+
+    /// load config, defaults only if missing
+    pub fn try_load() -> Result<Self, String> {
+        let path = app_path(CONFIG_FILENAME);
+        let serialized = match fs::read_to_string(&path) {
+            Ok(serialized) => serialized,
+            Err(_) => return Ok(Self::default()),
+        };
+
+        serde_json::from_str::<Config>(&serialized).map_err(|e| tr_args("config-invalid", &[("file", &path), ("error", &e)]))
+    }
+
+    /// remember the window geometry
+    pub fn save_window(geometry: WindowGeometry) {
+        let mut entries = serde_json::Map::new();
+        entries.insert("window".to_string(), serde_json::to_value(geometry).expect("シリアライズできませんでした"));
+        if let Err(e) = Self::save_entries(entries) {
+            eprintln!("{}", e);
+        }
+    }
+
+    /// remember the settings
+    pub fn save_settings(settings: &Settings) -> Result<(), String> {
+        match serde_json::to_value(settings).expect("シリアライズできませんでした") {
+            serde_json::Value::Object(entries) => Self::save_entries(entries),
+            _ => Ok(()),
+        }
+    }
+
+    // replace the given entries, the rest of the file is kept as written
+    fn save_entries(entries: serde_json::Map<String, serde_json::Value>) -> Result<(), String> {
+        let path = app_path(CONFIG_FILENAME);
+        let mut config = match fs::read_to_string(&path) {
+            Ok(serialized) => match serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&serialized) {
+                Ok(config) => config,
+                // leave a broken config for the user to fix
+                Err(e) => return Err(tr_args("config-invalid", &[("file", &path), ("error", &e)])),
+            },
+            Err(_) => serde_json::Map::new(),
+        };
+
+        config.extend(entries);
+        let serialized = serde_json::to_string_pretty(&config).expect("シリアライズできませんでした");
+        fs::write(&path, serialized).map_err(|e| tr_args("config-write-failed", &[("file", &path), ("error", &e)]))
+    }
+
+    /// data file name, relative paths are next to the executable
+    pub fn data_file(&self) -> String {
+        match self.settings.data_file.as_str() {
+            "" => app_path(self.storage.default_filename()),
+            filename => app_path(filename),
+        }
+    }
+}
+
+/// resolve a path relative to the executable
+pub fn app_path(filename: &str) -> String {
+    let path_buf = env::current_exe().expect("カレントパスを取得できませんでした");
+    if let Some(path) = path_buf.parent() {
+        let pb = path.join(filename);
+        if let Some(p) = pb.to_str() {
+            return p.to_string()
+        }
+    }
+
+    filename.to_string()
+}
@@ -0,0 +1,578 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, Utc};
+use druid::text::{RichText, RichTextBuilder};
+use druid::widget::prelude::*;
+use druid::widget::{CrossAxisAlignment, Flex, Label, LineBreaking, RawLabel, Scroll, TextBox};
+use druid::{Data, FontFamily, FontStyle, FontWeight, Lens, LensExt, Selector, WidgetExt};
+use std::process::Command;
+
+use crate::action::Action;
+use crate::i18n::{tr, tr_args};
+use crate::model::{Recurrence, Task, TaskId};
+use crate::settings::{make_heading, SMALL_TEXT_SIZE, TASK_TEXT_SIZE, TEXT_SIZE};
+use crate::theme;
+use crate::widget::Button;
+use crate::{make_button, TaskState, View, BLOCK_HEIGHT, BLOCK_SPACE, TASK_BLOCK_HEIGHT};
+
+// open a link clicked in the notes
+pub const OPEN_LINK: Selector<String> = Selector::new("tasking.detail.open-link");
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+const NOTES_HEIGHT: f64 = 140.0;
+
+/// task shown in the detail view, fields are saved when it is closed
+#[derive(Clone, Default, Data, Lens)]
+pub struct Detail {
+    pub task: Option<TaskId>,
+    notes: String,
+    tags: String,
+    due: String,
+    remind_at: String,
+    // texts as loaded, only the fields the user changed are written back
+    #[data(ignore)]
+    #[lens(ignore)]
+    loaded: Texts,
+}
+
+/// editable texts of the detail view
+#[derive(Clone, Default)]
+struct Texts {
+    notes: String,
+    tags: String,
+    due: String,
+    remind_at: String,
+}
+
+impl Detail {
+    fn texts(&self) -> Texts {
+        Texts {
+            notes: self.notes.to_string(),
+            tags: self.tags.to_string(),
+            due: self.due.to_string(),
+            remind_at: self.remind_at.to_string(),
+        }
+    }
+
+    fn parse_due(&self) -> Result<Option<NaiveDate>, chrono::ParseError> {
+        match self.due.trim() {
+            "" => Ok(None),
+            due => NaiveDate::parse_from_str(due, DATE_FORMAT).map(Some),
+        }
+    }
+
+    fn parse_remind_at(&self) -> Result<Option<NaiveDateTime>, chrono::ParseError> {
+        match self.remind_at.trim() {
+            "" => Ok(None),
+            remind_at => NaiveDateTime::parse_from_str(remind_at, TIME_FORMAT).map(Some),
+        }
+    }
+
+    // comma or space separated, a leading # is dropped
+    fn parse_tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in self.tags.split(|c: char| c == ',' || c == '、' || c.is_whitespace()) {
+            let tag = tag.trim_start_matches('#');
+            if !tag.is_empty() && !tags.iter().any(|other| other == tag) {
+                tags.push(tag.to_string());
+            }
+        }
+        tags
+    }
+}
+
+impl TaskState {
+    /// open the detail view of a task
+    pub fn show_detail(&mut self, id: &TaskId) {
+        let task = match self.tasks.tasks.iter().find(|task| task.equals(id)) {
+            Some(task) => task,
+            None => return,
+        };
+
+        self.detail = Detail {
+            task: Some(id.clone()),
+            notes: task.notes.to_string(),
+            tags: task.tags.join(", "),
+            due: task.due.map(|due| due.format(DATE_FORMAT).to_string()).unwrap_or_default(),
+            remind_at: task.remind_at.map(|remind_at| remind_at.format(TIME_FORMAT).to_string()).unwrap_or_default(),
+            ..Detail::default()
+        };
+        self.detail.loaded = self.detail.texts();
+        self.deleting = None;
+        self.view = View::Detail;
+    }
+
+    /// save the changed fields and go back to the list
+    pub fn close_detail(&mut self) {
+        self.set_view(View::List);
+    }
+
+    /// write the changed fields of the open task, the view stays open
+    pub fn save_detail(&mut self) {
+        if self.view != View::Detail {
+            return;
+        }
+        let id = match self.detail_task() {
+            Some(task) => task.id.clone(),
+            // removed while it was open
+            None => return,
+        };
+
+        // changes made elsewhere meanwhile, e.g. a snoozed reminder, stay unless the field was edited
+        let loaded = std::mem::replace(&mut self.detail.loaded, self.detail.texts());
+        if self.detail.notes != loaded.notes {
+            self.dispatch(Action::SetNotes(id.clone(), self.detail.notes.to_string()));
+        }
+        if self.detail.tags != loaded.tags {
+            self.dispatch(Action::SetTags(id.clone(), self.detail.parse_tags()));
+        }
+        // an invalid date keeps the old one
+        if self.detail.due != loaded.due {
+            if let Ok(due) = self.detail.parse_due() {
+                self.dispatch(Action::SetDue(id.clone(), due));
+            }
+        }
+        if self.detail.remind_at != loaded.remind_at {
+            if let Ok(remind_at) = self.detail.parse_remind_at() {
+                self.dispatch(Action::SetReminder(id, remind_at));
+            }
+        }
+    }
+
+    /// show the open task again after the tasks were replaced
+    pub fn reload_detail(&mut self) {
+        if self.view != View::Detail {
+            return;
+        }
+        match self.detail.task.clone() {
+            Some(id) if self.detail_task().is_some() => self.show_detail(&id),
+            // removed by the other side
+            _ => self.close_detail(),
+        }
+    }
+
+    /// repeat the open task on a weekday or stop it
+    fn toggle_detail_day(&mut self, day: usize) {
+        let (id, recurrence) = match self.detail_task() {
+            Some(task) => (task.id.clone(), Recurrence::toggle_day(&task.recurrence, day)),
+            None => return,
+        };
+        self.dispatch(Action::SetRecurrence(id, recurrence));
+
+        // repeating sets a due date, show it unless one is being typed
+        if self.detail.due.trim().is_empty() {
+            if let Some(due) = self.detail_task().and_then(|task| task.due) {
+                self.detail.due = due.format(DATE_FORMAT).to_string();
+                self.detail.loaded.due = self.detail.due.to_string();
+            }
+        }
+    }
+
+    fn detail_task(&self) -> Option<&Task> {
+        let id = self.detail.task.as_ref()?;
+        self.tasks.tasks.iter().find(|task| task.equals(id))
+    }
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.with_timezone(&Local).format(TIME_FORMAT).to_string()
+}
+
+/// inline markup of the notes
+#[derive(Debug, PartialEq)]
+enum Span {
+    Text(String),
+    Bold(String),
+    Italic(String),
+    Code(String),
+    Link(String, String),
+}
+
+// a marked up span at the start of text and the rest after it
+fn parse_marked(text: &str) -> Option<(Span, &str)> {
+    if let Some(rest) = text.strip_prefix("**") {
+        let (bold, rest) = rest.split_once("**")?;
+        return (!bold.is_empty()).then(|| (Span::Bold(bold.to_string()), rest));
+    }
+    if let Some(rest) = text.strip_prefix('*') {
+        let (italic, rest) = rest.split_once('*')?;
+        return (!italic.is_empty() && !italic.starts_with(' ')).then(|| (Span::Italic(italic.to_string()), rest));
+    }
+    if let Some(rest) = text.strip_prefix('`') {
+        let (code, rest) = rest.split_once('`')?;
+        return (!code.is_empty()).then(|| (Span::Code(code.to_string()), rest));
+    }
+    if let Some(rest) = text.strip_prefix('[') {
+        // [label](url), other targets stay plain text
+        let (label, rest) = rest.split_once("](")?;
+        let (url, rest) = rest.split_once(')')?;
+        return (!label.is_empty() && is_web_link(url) && !url.contains(char::is_whitespace))
+            .then(|| (Span::Link(label.to_string(), url.to_string()), rest));
+    }
+    if text.starts_with("https://") || text.starts_with("http://") {
+        let end = text.find(char::is_whitespace).unwrap_or(text.len());
+        // punctuation after a bare url ends the sentence
+        let url = text[..end].trim_end_matches(|c: char| ".,;:!?)、。".contains(c));
+        return is_web_link(url).then(|| (Span::Link(url.to_string(), url.to_string()), &text[url.len()..]));
+    }
+    None
+}
+
+// only pages and mail are opened, never local files or programs
+fn is_web_link(url: &str) -> bool {
+    ["https://", "http://", "mailto:"].iter().any(|scheme| url.starts_with(scheme) && url.len() > scheme.len())
+}
+
+fn parse_line(line: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut text = String::new();
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        match parse_marked(rest) {
+            Some((span, after)) => {
+                if !text.is_empty() {
+                    spans.push(Span::Text(std::mem::take(&mut text)));
+                }
+                spans.push(span);
+                rest = after;
+            },
+            None => {
+                text.push(c);
+                rest = &rest[c.len_utf8()..];
+            },
+        }
+    }
+    if !text.is_empty() {
+        spans.push(Span::Text(text));
+    }
+    spans
+}
+
+/// notes with headings, list items, links, emphasis and code rendered
+pub fn render_notes(notes: &str) -> RichText {
+    let mut builder = RichTextBuilder::new();
+    for (index, line) in notes.lines().enumerate() {
+        if index > 0 {
+            builder.push("\n");
+        }
+
+        let heading = line.trim_start_matches('#');
+        let is_heading = heading.len() < line.len() && heading.starts_with(' ');
+        let line = match (is_heading, line.strip_prefix("- ").or_else(|| line.strip_prefix("* "))) {
+            (true, _) => heading.trim_start(),
+            (false, Some(item)) => {
+                builder.push("• ");
+                item
+            },
+            (false, None) => line,
+        };
+
+        for span in parse_line(line) {
+            let mut attributes = match &span {
+                Span::Text(text) | Span::Bold(text) | Span::Italic(text) | Span::Code(text) => builder.push(text),
+                Span::Link(label, _) => builder.push(label),
+            };
+            if is_heading {
+                attributes.weight(FontWeight::BOLD);
+            }
+            match span {
+                Span::Text(_) => (),
+                Span::Bold(_) => {
+                    attributes.weight(FontWeight::BOLD);
+                },
+                Span::Italic(_) => {
+                    attributes.style(FontStyle::Italic);
+                },
+                Span::Code(_) => {
+                    attributes.font_family(FontFamily::MONOSPACE);
+                },
+                Span::Link(_, url) => {
+                    attributes.underline(true).text_color(theme::PRIMARY).link(OPEN_LINK.with(url));
+                },
+            }
+        }
+    }
+    builder.build()
+}
+
+/// open a link in the default browser
+pub fn open_link(url: &str) {
+    if !is_web_link(url) {
+        eprintln!("{} は開けないリンクです", url);
+        return;
+    }
+    if let Err(e) = opener(url).spawn() {
+        eprintln!("{} を開けませんでした: {}", url, e);
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn opener(url: &str) -> Command {
+    let mut command = Command::new("explorer");
+    command.arg(url);
+    command
+}
+
+#[cfg(target_os = "macos")]
+fn opener(url: &str) -> Command {
+    let mut command = Command::new("open");
+    command.arg(url);
+    command
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn opener(url: &str) -> Command {
+    let mut command = Command::new("xdg-open");
+    command.arg(url);
+    command
+}
+
+fn make_day_button(day: usize) -> impl Widget<TaskState> {
+    Button::new(Recurrence::day_name(day), move |data: &TaskState, env: &Env| {
+        match data.detail_task().map(|task| Recurrence::has_day(&task.recurrence, day)).unwrap_or(false) {
+            true => env.get(theme::PRIMARY),
+            false => env.get(theme::FAINT),
+        }
+    })
+        .with_text_size(SMALL_TEXT_SIZE)
+        .with_text_color(theme::ON_ACCENT)
+        .on_click(move |_, data: &mut TaskState, _| {
+            data.toggle_detail_day(day);
+        })
+        .expand_width()
+        .fix_height(TASK_BLOCK_HEIGHT)
+}
+
+pub fn make_detail_view() -> impl Widget<TaskState> {
+    let mut days = Flex::row();
+    for day in 0..7 {
+        if day > 0 {
+            days.add_spacer(BLOCK_SPACE / 2.0);
+        }
+        days.add_flex_child(make_day_button(day), 1.0);
+    }
+
+    let form = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(
+            Label::new(|data: &TaskState, _: &Env| data.detail_task().map(|task| task.content.to_string()).unwrap_or_default())
+                .with_text_size(TEXT_SIZE)
+                .with_line_break_mode(LineBreaking::WordWrap)
+                .expand_width(),
+        )
+        .with_child(
+            Label::new(|data: &TaskState, _: &Env| {
+                let task = match data.detail_task() {
+                    Some(task) => task,
+                    None => return "".to_string(),
+                };
+                let mut times = vec![task.status.to_string()];
+                if let Some(created) = &task.created {
+                    times.push(tr_args("detail-created", &[("time", &format_time(created))]));
+                }
+                if let Some(completed) = &task.completed_at() {
+                    times.push(tr_args("detail-completed", &[("time", &format_time(completed))]));
+                }
+                times.join("  ")
+            })
+                .with_text_size(SMALL_TEXT_SIZE)
+                .with_text_color(theme::MUTED)
+                .with_line_break_mode(LineBreaking::WordWrap),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(make_heading("detail-notes"))
+        .with_child(
+            TextBox::multiline()
+                .with_text_size(TASK_TEXT_SIZE)
+                .expand_width()
+                .fix_height(NOTES_HEIGHT)
+                .lens(TaskState::detail.then(Detail::notes)),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(make_heading("detail-preview"))
+        .with_child(
+            RawLabel::new()
+                .with_text_size(TASK_TEXT_SIZE)
+                .with_line_break_mode(LineBreaking::WordWrap)
+                .lens(TaskState::detail.map(|detail: &Detail| render_notes(&detail.notes), |_, _| ()))
+                .expand_width(),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(make_heading("detail-tags"))
+        .with_child(
+            TextBox::new()
+                .with_placeholder(tr("detail-tags-placeholder"))
+                .with_text_size(TASK_TEXT_SIZE)
+                .expand_width()
+                .lens(TaskState::detail.then(Detail::tags)),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(make_heading("detail-due"))
+        .with_child(
+            TextBox::new()
+                .with_placeholder("2024-01-31")
+                .with_text_size(TASK_TEXT_SIZE)
+                .expand_width()
+                .lens(TaskState::detail.then(Detail::due)),
+        )
+        .with_child(
+            Label::new(|data: &TaskState, _: &Env| match data.detail.parse_due() {
+                Ok(_) => "".to_string(),
+                Err(_) => tr("detail-due-invalid"),
+            })
+                .with_text_size(SMALL_TEXT_SIZE)
+                .with_text_color(theme::DANGER),
+        )
+        .with_child(make_heading("detail-remind-at"))
+        .with_child(
+            TextBox::new()
+                .with_placeholder("2024-01-31 15:00")
+                .with_text_size(TASK_TEXT_SIZE)
+                .expand_width()
+                .lens(TaskState::detail.then(Detail::remind_at)),
+        )
+        .with_child(
+            Label::new(|data: &TaskState, _: &Env| match data.detail.parse_remind_at() {
+                Ok(_) => "".to_string(),
+                Err(_) => tr("detail-remind-at-invalid"),
+            })
+                .with_text_size(SMALL_TEXT_SIZE)
+                .with_text_color(theme::DANGER),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(make_heading("detail-weekdays"))
+        .with_child(days)
+        .with_spacer(BLOCK_SPACE)
+        .with_child(make_heading("detail-history"))
+        .with_child(
+            Label::new(|data: &TaskState, _: &Env| {
+                let history = data.detail_task().map(|task| task.status_history.as_slice()).unwrap_or_default();
+                match history.is_empty() {
+                    true => tr("detail-no-history"),
+                    false => history.iter()
+                        .map(|change| format!("{}  {}", format_time(&change.at), change.status))
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                }
+            })
+                .with_text_size(TASK_TEXT_SIZE),
+        );
+
+    Flex::column()
+        .with_flex_child(
+            Scroll::new(form.padding((0.0, 0.0, BLOCK_SPACE, 0.0)))
+                .vertical()
+                .expand(),
+            1.0,
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(
+            make_button::<TaskState>(tr("back"), TEXT_SIZE, theme::DARK)
+                .on_click(|_, data, _| {
+                    data.close_detail();
+                })
+                .expand_width()
+                .fix_height(BLOCK_HEIGHT),
+        )
+        .padding(BLOCK_SPACE)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use super::*;
+    use crate::config::{Config, Storage};
+    use crate::model::TaskRepository;
+
+    fn state(path: &str) -> TaskState {
+        let repository = TaskRepository::open(path.to_string(), Storage::Json).expect("repository").keep_backups(0);
+        TaskState::new(repository, &Config::default())
+    }
+
+    fn text(text: &str) -> Span {
+        Span::Text(text.to_string())
+    }
+
+    fn link(label: &str, url: &str) -> Span {
+        Span::Link(label.to_string(), url.to_string())
+    }
+
+    #[test]
+    fn parse_emphasis_and_code() {
+        assert_eq!(
+            parse_line("a **b** *c* `d`"),
+            vec![
+                text("a "),
+                Span::Bold("b".to_string()),
+                text(" "),
+                Span::Italic("c".to_string()),
+                text(" "),
+                Span::Code("d".to_string()),
+            ],
+        );
+    }
+
+    #[test]
+    fn parse_unclosed_markers_as_text() {
+        assert_eq!(parse_line("**a"), vec![text("**a")]);
+        assert_eq!(parse_line("2 * 3 = 6"), vec![text("2 * 3 = 6")]);
+        assert_eq!(parse_line("`a"), vec![text("`a")]);
+        assert_eq!(parse_line("[a](b"), vec![text("[a](b")]);
+    }
+
+    #[test]
+    fn parse_links() {
+        assert_eq!(parse_line("[site](https://example.com) x"), vec![link("site", "https://example.com"), text(" x")]);
+        assert_eq!(parse_line("[mail](mailto:a@example.com)"), vec![link("mail", "mailto:a@example.com")]);
+    }
+
+    #[test]
+    fn parse_bare_url_without_trailing_punctuation() {
+        assert_eq!(
+            parse_line("see http://example.com/a."),
+            vec![text("see "), link("http://example.com/a", "http://example.com/a"), text(".")],
+        );
+        assert_eq!(
+            parse_line("(https://example.com)、"),
+            vec![text("("), link("https://example.com", "https://example.com"), text(")、")],
+        );
+    }
+
+    #[test]
+    fn parse_other_link_targets_as_text() {
+        assert_eq!(parse_line("[x](file:///etc/passwd)"), vec![text("[x](file:///etc/passwd)")]);
+        assert_eq!(parse_line(r"[x](C:\Windows\System32\calc.exe)"), vec![text(r"[x](C:\Windows\System32\calc.exe)")]);
+        assert_eq!(parse_line("[x](https://)"), vec![text("[x](https://)")]);
+    }
+
+    #[test]
+    fn parse_tags_drops_duplicates_and_hashes() {
+        let detail = Detail {
+            tags: "#work, home 、work  urgent".to_string(),
+            ..Detail::default()
+        };
+        assert_eq!(detail.parse_tags(), vec!["work", "home", "urgent"]);
+    }
+
+    #[test]
+    fn closing_keeps_changes_made_elsewhere() {
+        let path = env::temp_dir().join(format!("tasking-detail-{}.json", TaskId::generate())).to_string_lossy().to_string();
+        let mut state = state(&path);
+        state.dispatch(Action::Add("task".to_string()));
+        let id = state.tasks.tasks[0].id.clone();
+
+        state.show_detail(&id);
+        // snoozed while the detail is open
+        let at = NaiveDate::from_ymd_opt(2030, 1, 31).and_then(|date| date.and_hms_opt(15, 0, 0));
+        state.dispatch(Action::SetReminder(id.clone(), at));
+        state.detail.notes = "notes".to_string();
+        state.close_detail();
+
+        assert_eq!(state.tasks.tasks[0].remind_at, at);
+        assert_eq!(state.tasks.tasks[0].notes, "notes");
+
+        state.autosave.flush();
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,46 @@
+use druid::widget::prelude::*;
+use druid::widget::Controller;
+
+use crate::config::{Config, WindowGeometry};
+use crate::TaskState;
+
+/// remembers the window size and position, saved when the window closes
+pub struct Geometry {
+    last: Option<WindowGeometry>,
+}
+
+impl Geometry {
+    /// construct
+    pub fn new() -> Self {
+        Self {
+            last: None,
+        }
+    }
+
+    fn record(&mut self, ctx: &EventCtx) {
+        let size = ctx.window().get_size();
+        let position = ctx.window().get_position();
+        self.last = Some(WindowGeometry {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        });
+    }
+}
+
+impl<W: Widget<TaskState>> Controller<TaskState, W> for Geometry {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut TaskState, env: &Env) {
+        match event {
+            Event::WindowSize(_) | Event::WindowCloseRequested => self.record(ctx),
+            Event::WindowDisconnected => {
+                if let Some(geometry) = self.last.take() {
+                    Config::save_window(geometry);
+                }
+            },
+            _ => (),
+        }
+
+        child.event(ctx, event, data, env)
+    }
+}
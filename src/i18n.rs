@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+// fluent style "key = value" resources, { $name } is replaced by an argument
+const RESOURCES: [&str; 2] = [
+    include_str!("../locales/ja.ftl"),
+    include_str!("../locales/en.ftl"),
+];
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static BUNDLES: OnceLock<Vec<HashMap<&'static str, String>>> = OnceLock::new();
+
+/// language of the ui strings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Locale {
+    Japanese,
+    English,
+}
+
+impl Locale {
+    pub fn locales() -> Vec<Self> {
+        vec![Self::Japanese, Self::English]
+    }
+
+    /// locale of the os, english unless it is japanese
+    pub fn system() -> Self {
+        match sys_locale::get_locale() {
+            Some(locale) if locale.starts_with("ja") => Self::Japanese,
+            _ => Self::English,
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Self::Japanese => 0,
+            Self::English => 1,
+        }
+    }
+}
+
+fn parse(source: &'static str) -> HashMap<&'static str, String> {
+    source.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| {
+            let value = value.trim();
+            // a quoted literal keeps its spaces
+            let value = value.strip_prefix("{ \"")
+                .and_then(|value| value.strip_suffix("\" }"))
+                .unwrap_or(value);
+            (key.trim(), value.replace("\\n", "\n"))
+        })
+        .collect()
+}
+
+fn lookup(locale: Locale, key: &str) -> Option<&'static String> {
+    BUNDLES.get_or_init(|| RESOURCES.iter().map(|source| parse(source)).collect())[locale.index()].get(key)
+}
+
+/// switch the language of later lookups
+pub fn set_locale(locale: Locale) {
+    CURRENT.store(locale.index(), Ordering::Relaxed);
+}
+
+/// current language
+pub fn locale() -> Locale {
+    match CURRENT.load(Ordering::Relaxed) {
+        0 => Locale::Japanese,
+        _ => Locale::English,
+    }
+}
+
+/// translation in a language, japanese or the key itself when missing
+pub fn tr_in(locale: Locale, key: &str) -> String {
+    lookup(locale, key)
+        .or_else(|| lookup(Locale::Japanese, key))
+        .cloned()
+        .unwrap_or_else(|| key.to_string())
+}
+
+/// translation in the current language
+pub fn tr(key: &str) -> String {
+    tr_in(locale(), key)
+}
+
+/// translation in a language with its { $name } arguments filled in
+pub fn tr_args_in(locale: Locale, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter().fold(tr_in(locale, key), |text, (name, value)| {
+        text.replace(&format!("{{ ${} }}", name), &value.to_string())
+    })
+}
+
+/// translation in the current language with its { $name } arguments filled in
+pub fn tr_args(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    tr_args_in(locale(), key, args)
+}
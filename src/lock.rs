@@ -0,0 +1,113 @@
+use druid::{commands, ExtEventSink, Target, WindowId};
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+const FOCUS_REQUEST: &str = "focus";
+const FOCUS_REPLY: &str = "tasking";
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// result of locking a data file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LockStatus {
+    /// this instance owns the data file
+    Locked,
+    /// another instance owns the data file and was brought to front
+    Focused,
+    /// another instance may own the data file, open without saving
+    ReadOnly,
+}
+
+/// advisory lock file next to the data file, removed when released or dropped
+pub struct InstanceLock {
+    path: PathBuf,
+    listener: TcpListener,
+}
+
+impl InstanceLock {
+    /// lock the data file or hand over to the instance holding it
+    pub fn acquire(filename: &str) -> Result<Self, LockStatus> {
+        let path = PathBuf::from(format!("{}.lock", filename));
+
+        match Self::create(&path) {
+            Ok(lock) => return Ok(lock),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => (),
+            Err(_) => return Err(LockStatus::ReadOnly),
+        }
+
+        let port = fs::read_to_string(&path).ok().and_then(|port| port.trim().parse::<u16>().ok());
+        match port.map(request_focus) {
+            Some(Ok(true)) => Err(LockStatus::Focused),
+            Some(Err(e)) if e.kind() != ErrorKind::ConnectionRefused => Err(LockStatus::ReadOnly),
+            _ => {
+                // lock left behind by a crashed instance
+                let _ = fs::remove_file(&path);
+                Self::create(&path).map_err(|_| LockStatus::ReadOnly)
+            },
+        }
+    }
+
+    /// bring the main window to front when another launch asks for it
+    pub fn serve(&self, sink: ExtEventSink, window: WindowId) {
+        let listener = match self.listener.try_clone() {
+            Ok(listener) => listener,
+            Err(_) => return,
+        };
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+
+                let mut request = String::new();
+                if BufReader::new(&stream).read_line(&mut request).is_err() || request.trim() != FOCUS_REQUEST {
+                    continue;
+                }
+
+                let _ = writeln!(stream, "{}", FOCUS_REPLY);
+                if sink.submit_command(commands::SHOW_WINDOW, (), Target::Window(window)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// remove the lock file, threads holding the lock may outlive the app
+    pub fn release(&self) {
+        let _ = fs::remove_file(&self.path);
+    }
+
+    fn create(path: &Path) -> std::io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let mut file = fs::OpenOptions::new().write(true).create_new(true).open(path)?;
+        writeln!(file, "{}", listener.local_addr()?.port())?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            listener: listener,
+        })
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+/// ask the instance listening on port to show its window
+fn request_focus(port: u16) -> std::io::Result<bool> {
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+    writeln!(stream, "{}", FOCUS_REQUEST)?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply)?;
+    Ok(reply.trim() == FOCUS_REPLY)
+}
@@ -1,28 +1,75 @@
 #![windows_subsystem = "windows"]
 
 use druid::im::{Vector};
-use druid::kurbo::{Insets};
+use druid::kurbo::{Arc, Circle, Insets, Point, Vec2};
 use druid::widget::prelude::*;
-use druid::widget::{Flex, Label, TextBox, List, Scroll, ViewSwitcher, Painter, FlexParams, CrossAxisAlignment};
-use druid::{commands, theme, lens, AppDelegate, AppLauncher, Command, Handled, Target, DelegateCtx, Data, Lens, Color, Widget, LensExt, WidgetExt, WindowDesc};
+use druid::widget::{EnvScope, Either, Flex, SizedBox, Label, LabelText, LineBreaking, TextBox, List, Scroll, ViewSwitcher, Painter, FlexParams, CrossAxisAlignment};
+use druid::{commands, lens, AppDelegate, AppLauncher, Command, Handled, Target, DelegateCtx, Data, Key, Lens, Color, Widget, LensExt, WidgetExt, WindowDesc, WindowConfig, WindowId, FileDialogOptions, FileSpec, ExtEventSink, Selector, UnitPoint};
+use chrono::{DateTime, Utc};
+use std::env;
+use std::fs;
+use std::process;
+use std::thread;
 
+mod action;
+mod api;
+mod autosave;
+mod backup;
+mod board;
+mod cli;
+mod config;
+mod detail;
+mod geometry;
+mod i18n;
+mod lock;
+mod menu;
+mod merge;
 mod model;
-use model::{TaskStatus, Task, Tasks, TaskRepository};
+mod pomodoro;
+mod reminder;
+mod settings;
+mod stats;
+mod store;
+mod sync;
+mod theme;
+mod tracking;
+mod watch;
+mod widget;
+use action::Action;
+use autosave::Autosave;
+use backup::BackupItem;
+use config::{app_path, Config, Storage};
+use detail::Detail;
+use lock::LockStatus;
+use pomodoro::{Phase, Pomodoro};
+use reminder::{DesktopNotifier, Reminders};
+use settings::{Settings, LARGE_TEXT_SIZE, SMALL_TEXT_SIZE, TASK_TEXT_SIZE, TEXT_SIZE};
+use stats::Stats;
+use geometry::Geometry;
+use i18n::{tr, tr_args};
+use theme::{status_color, Theme};
+use tracking::Clock;
+use widget::Button;
+use model::{TaskStatus, TaskId, Task, Tasks, SaveTasks, History, TaskRepository, Recurrence};
+use watch::{ExternalConflict, Resolution, Watcher};
 
-const TEXT_SIZE: f64 = 18.0;
 const BLOCK_HEIGHT: f64 = 38.0;
 const BORDER_RADIUS: f64 = 4.0;
 const WINDOW_HEIGHT: f64 = 500.0;
 const WINDOW_WIDTH: f64 = 400.0;
 const BLOCK_SPACE: f64 = 10.0;
-const INNER_WIDTH: f64 = WINDOW_WIDTH - (BLOCK_SPACE * 2.0);
-const LINE_HEIGHT: f64 = BLOCK_HEIGHT + (BLOCK_SPACE * 2.0);
+// the layout is laid out for this size at the smallest
+const MIN_WINDOW_SIZE: (f64, f64) = (320.0, 360.0);
 
-const TASK_TEXT_SIZE: f64 = 14.0;
+const BOARD_WIDTH: f64 = 800.0;
 const TASK_BLOCK_HEIGHT: f64 = 24.0;
 
-// save tasks json file
-const SAVE_FILENAME: &str = "task.json";
+// unlock the encrypted data file with the entered passphrase
+const UNLOCK: Selector = Selector::new("tasking.unlock");
+
+const JSON_FILE: FileSpec = FileSpec::new("JSON", &["json"]);
+const TEXT_FILE: FileSpec = FileSpec::new("Text", &["txt", "md"]);
+const SQLITE_FILE: FileSpec = FileSpec::new("SQLite", &["db", "sqlite", "sqlite3"]);
 
 #[derive(Clone, Data, Lens)]
 struct TaskState {
@@ -30,96 +77,671 @@ struct TaskState {
     memo: String,
     tasks: Tasks,
     repository: TaskRepository,
-    setting: bool,
+    autosave: Autosave,
+    history: History,
+    filter: Option<TaskStatus>,
+    conflict: Option<ExternalConflict>,
+    backups: Vector<BackupItem>,
+    passphrase: String,
+    unlock_failed: bool,
+    view: View,
+    single_running: bool,
+    // moves while a task is running
+    #[data(eq)]
+    now: DateTime<Utc>,
+    pomodoro: Pomodoro,
+    // card picked up on the board
+    dragging: Option<TaskId>,
+    // waits for a second click when deletes are confirmed
+    deleting: Option<TaskId>,
+    detail: Detail,
+    theme: Theme,
+    settings: Settings,
 }
 
-#[cfg(target_os = "windows")]
-fn window_height() -> f64 {
-    WINDOW_HEIGHT + (BLOCK_SPACE * 4.0)
+#[derive(Clone, Copy, PartialEq, Data)]
+enum View {
+    Unlock,
+    List,
+    Memo,
+    Backups,
+    Pomodoro,
+    Stats,
+    Board,
+    Settings,
+    Detail,
 }
 
-#[cfg(target_os = "macos")]
+impl TaskState {
+    /// app state over a repository
+    fn new(repository: TaskRepository, config: &Config) -> Self {
+        // encrypted data waits for the passphrase
+        let view = match repository.is_locked() {
+            true => View::Unlock,
+            false => View::List,
+        };
+
+        Self {
+            content: "".into(),
+            memo: "".into(),
+            tasks: Tasks::from_save_tasks(repository.load()),
+            autosave: Autosave::new(repository.clone(), config.settings.autosave_delay()),
+            repository: repository,
+            history: History::new(),
+            filter: None,
+            conflict: None,
+            backups: Vector::new(),
+            passphrase: "".into(),
+            unlock_failed: false,
+            view: view,
+            single_running: config.single_running,
+            now: Utc::now(),
+            pomodoro: Pomodoro::new(config.pomodoro.work_minutes, config.pomodoro.break_minutes),
+            dragging: None,
+            deleting: None,
+            detail: Detail::default(),
+            theme: Theme::load(config.settings.theme),
+            settings: config.settings.clone(),
+        }
+    }
+
+    /// switch views, leaving the detail view saves its fields
+    fn set_view(&mut self, view: View) {
+        if self.view == View::Detail && view != View::Detail {
+            self.save_detail();
+            self.detail = Detail::default();
+        }
+        self.view = view;
+    }
+
+    /// toggle memo view
+    fn toggle_memo(&mut self) {
+        if self.view == View::Memo {
+            self.set_view(View::List);
+            return;
+        }
+
+        let text = self.tasks.to_text();
+        if text.len() > 0 {
+            self.memo = format!("--- task ---\n{}\n------------", text).to_string();
+        }
+
+        self.set_view(View::Memo);
+    }
+
+    /// toggle kanban board view
+    fn toggle_board(&mut self) {
+        self.dragging = None;
+        self.set_view(match self.view {
+            View::Board => View::List,
+            _ => View::Board,
+        });
+    }
+
+    /// toggle statistics view
+    fn toggle_stats(&mut self) {
+        self.set_view(match self.view {
+            View::Stats => View::List,
+            _ => View::Stats,
+        });
+    }
+
+    /// remove a task, the first click only asks when deletes are confirmed
+    fn request_remove(&mut self, id: &TaskId) {
+        if self.settings.confirm_delete && self.deleting.as_ref() != Some(id) {
+            self.deleting = Some(id.clone());
+            return;
+        }
+
+        self.deleting = None;
+        self.dispatch(Action::Remove(id.clone()));
+    }
+
+    /// show backups compared with the current tasks
+    fn show_backups(&mut self) {
+        self.backups = self.repository.backup_items(&self.tasks.to_save_tasks()).into();
+        self.set_view(View::Backups);
+    }
+}
+
+// the menu bar takes room inside the window except on macos
 fn window_height() -> f64 {
-    WINDOW_HEIGHT
+    match cfg!(target_os = "macos") {
+        true => WINDOW_HEIGHT,
+        false => WINDOW_HEIGHT + (BLOCK_SPACE * 4.0),
+    }
 }
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+    if cli::run(&args) {
+        return;
+    }
+
+    let config = Config::load();
+    i18n::set_locale(config.settings.language.locale());
+    if config.storage == Storage::Sqlite {
+        // one-shot migration on first use
+        cli::migrate_once(&app_path(Storage::Json.default_filename()), &config.data_file());
+    }
+
     // describe the main window
-    let main_window = WindowDesc::new(make_widget())
-        .title("Tasking!")
-        .resizable(false)
+    let mut main_window = WindowDesc::new(configured(make_widget()))
+        .title(|data: &TaskState, _: &Env| match data.repository.is_read_only() {
+            true => tr("app-read-only"),
+            false => "Tasking!".to_string(),
+        })
+        .menu(menu::make_menu)
+        .resizable(true)
+        .with_min_size(MIN_WINDOW_SIZE)
         .window_size((WINDOW_WIDTH, window_height()));
+    if let Some(geometry) = &config.window {
+        // reopen where the window was closed
+        main_window = main_window
+            .window_size((geometry.width, geometry.height))
+            .set_position((geometry.x, geometry.y));
+    }
+    let main_window_id = main_window.id;
 
-    let repository = TaskRepository::new(SAVE_FILENAME.to_string());
+    let mut repository = match TaskRepository::open(config.data_file(), config.storage) {
+        Ok(repository) => repository.keep_backups(config.backups),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        },
+    };
+    if let Some(sync) = &config.sync {
+        repository = repository.with_sync(sync);
+    }
+    if repository.lock() == LockStatus::Focused {
+        // the running instance takes over
+        return;
+    }
 
     // create the initial app state
-    let initial_state: TaskState = TaskState {
-        content: "".into(),
-        memo: "".into(),
-        tasks: Tasks::from_save_tasks(repository.load()),
-        repository: repository,
-        setting: false,
-    };
+    let initial_state = TaskState::new(repository, &config);
+
+    let launcher = AppLauncher::with_window(main_window);
+    let sink = launcher.get_external_handle();
+
+    if let Some(lock) = initial_state.repository.instance_lock() {
+        lock.serve(sink.clone(), main_window_id);
+    }
+    if let Some(api) = &config.api {
+        if let Err(e) = api::serve(api.port, sink.clone()) {
+            eprintln!("API を開始できませんでした: {}", e);
+        }
+    }
 
     // start the application. Here we pass in the application state.
-    AppLauncher::with_window(main_window)
-        .delegate(Delegate)
+    let initial_theme = initial_state.theme.clone();
+    let initial_settings = initial_state.settings.clone();
+    launcher
+        .delegate(Delegate {
+            main_window: main_window_id,
+            watcher: Watcher::start(initial_state.repository.clone(), sink.clone()),
+            sink: sink,
+        })
         .log_to_console()
-        .configure_env(|env, _| {
-            env.set(theme::WINDOW_BACKGROUND_COLOR, Color::WHITE);
-            env.set(theme::CURSOR_COLOR, Color::BLACK);
-            env.set(theme::BACKGROUND_LIGHT, Color::rgba8(240, 240, 240, 0));
-            env.set(theme::TEXT_COLOR, Color::BLACK);
-            env.set(theme::TEXTBOX_BORDER_RADIUS, BORDER_RADIUS);
-            env.set(theme::TEXTBOX_INSETS, Insets::new(8.5, 8.5, 8.5, 8.5));
+        .configure_env(move |env, _| {
+            initial_theme.configure(env);
+            initial_settings.configure(env);
+            env.set(druid::theme::TEXTBOX_BORDER_RADIUS, BORDER_RADIUS);
+            env.set(druid::theme::BUTTON_BORDER_RADIUS, BORDER_RADIUS);
+            env.set(druid::theme::TEXTBOX_INSETS, Insets::new(8.5, 8.5, 8.5, 8.5));
         })
         .launch(initial_state)
         .expect("アプリケーションを起動できませんでした");
 }
 
-struct Delegate;
+struct Delegate {
+    main_window: WindowId,
+    // polls the data file while alive
+    #[allow(dead_code)]
+    watcher: Watcher,
+    sink: ExtEventSink,
+}
 
 impl AppDelegate<TaskState> for Delegate {
     fn command(
         &mut self,
         ctx: &mut DelegateCtx,
-        _: Target,
+        target: Target,
         cmd: &Command,
-        _: &mut TaskState,
+        data: &mut TaskState,
         _: &Env,
     ) -> Handled {
-        if cmd.is(commands::CLOSE_WINDOW) {
+        if let Some(call) = cmd.get(api::API_CALL) {
+            let reply = data.answer(call.request.clone());
+            call.reply(reply);
+            return Handled::Yes;
+        }
+
+        if cmd.is(commands::QUIT_APP) {
+            // write pending changes before exit
+            if data.view == View::Detail {
+                data.close_detail();
+            }
+            data.autosave.flush();
+            if let Some(lock) = data.repository.instance_lock() {
+                lock.release();
+            }
+            return Handled::No;
+        }
+
+        if cmd.is(commands::CLOSE_WINDOW) && target != Target::Window(self.main_window) {
+            return Handled::No;
+        }
+        if cmd.is(commands::CLOSE_WINDOW) || cmd.is(menu::QUIT) {
             ctx.submit_command(commands::QUIT_APP);
             return Handled::Yes;
         }
+
+        // file
+        if cmd.is(menu::IMPORT) {
+            let options = FileDialogOptions::new()
+                .allowed_types(vec![JSON_FILE, TEXT_FILE])
+                .accept_command(menu::IMPORT_FILE);
+            ctx.submit_command(commands::SHOW_OPEN_PANEL.with(options).to(target));
+            return Handled::Yes;
+        }
+        if let Some(file) = cmd.get(menu::IMPORT_FILE) {
+            if let Ok(text) = fs::read_to_string(file.path()) {
+                let tasks = match serde_json::from_str::<SaveTasks>(&text) {
+                    Ok(tasks) => tasks,
+                    Err(_) => SaveTasks::from_text(&text),
+                };
+                data.dispatch(Action::Import(tasks.tasks));
+            }
+            return Handled::Yes;
+        }
+        if cmd.is(menu::EXPORT) {
+            let options = FileDialogOptions::new()
+                .allowed_types(vec![TEXT_FILE])
+                .default_name("task.md")
+                .accept_command(menu::EXPORT_FILE);
+            ctx.submit_command(commands::SHOW_SAVE_PANEL.with(options).to(target));
+            return Handled::Yes;
+        }
+        if let Some(file) = cmd.get(menu::EXPORT_FILE) {
+            if let Err(e) = fs::write(file.path(), data.tasks.to_text()) {
+                show_message(ctx, &tr("export-failed-title"), format!("{}\n{}", file.path().display(), e));
+            }
+            return Handled::Yes;
+        }
+        if cmd.is(menu::OPEN_DATA_FILE) {
+            let options = FileDialogOptions::new()
+                .allowed_types(vec![JSON_FILE, SQLITE_FILE])
+                .accept_command(menu::OPEN_DATA_FILE_PATH);
+            ctx.submit_command(commands::SHOW_OPEN_PANEL.with(options).to(target));
+            return Handled::Yes;
+        }
+        if let Some(file) = cmd.get(menu::OPEN_DATA_FILE_PATH) {
+            if let Some(path) = file.path().to_str() {
+                if let Err(e) = self.open_data_file(data, path.to_string(), Storage::from_filename(path).unwrap_or(Storage::Json)) {
+                    show_message(ctx, &tr("open-failed-title"), e);
+                }
+            }
+            return Handled::Yes;
+        }
+
+        if cmd.is(UNLOCK) {
+            if data.repository.unlock(&data.passphrase) {
+                data.tasks = Tasks::from_save_tasks(data.repository.load());
+                data.unlock_failed = false;
+                data.view = View::List;
+            } else {
+                data.unlock_failed = true;
+            }
+            data.passphrase = "".to_string();
+            return Handled::Yes;
+        }
+
+        if cmd.is(menu::SHOW_BACKUPS) {
+            data.show_backups();
+            return Handled::Yes;
+        }
+        if let Some(path) = cmd.get(backup::RESTORE_BACKUP) {
+            if let Some(tasks) = data.repository.load_backup(path) {
+                data.dispatch(Action::Replace(Tasks::from_save_tasks(tasks)));
+                data.view = View::List;
+            }
+            return Handled::Yes;
+        }
+
+        // external changes
+        if let Some(tasks) = cmd.get(watch::EXTERNAL_CHANGE) {
+            // the open detail counts as a local change
+            data.save_detail();
+            let mine = data.tasks.to_save_tasks();
+            let merged = merge::merge(&data.repository.synced_tasks(), &mine, tasks);
+            if merged.is_clean() {
+                // combine quietly, only write back if local changes remain
+                data.repository.mark_synced(tasks);
+                data.dispatch(Action::Merge(Tasks::from_save_tasks(merged.tasks)));
+                data.reload_detail();
+            } else {
+                data.conflict = Some(ExternalConflict {
+                    theirs: Tasks::from_save_tasks(tasks.clone()),
+                    merged: Tasks::from_save_tasks(merged.tasks),
+                    conflicts: merged.conflicts.into(),
+                });
+            }
+            return Handled::Yes;
+        }
+        if let Some(resolution) = cmd.get(watch::RESOLVE_CONFLICT) {
+            if let Some(conflict) = data.conflict.take() {
+                data.repository.mark_synced(&conflict.theirs.to_save_tasks());
+                match resolution {
+                    Resolution::Theirs => data.dispatch(Action::Merge(conflict.theirs)),
+                    Resolution::Merged => data.dispatch(Action::Merge(conflict.merged)),
+                    // the file holds theirs now
+                    Resolution::Mine => data.autosave.save(data.tasks.to_save_tasks()),
+                }
+                data.reload_detail();
+            }
+            return Handled::Yes;
+        }
+
+        // edit
+        if cmd.is(menu::UNDO) {
+            data.dispatch(Action::Undo);
+            return Handled::Yes;
+        }
+        if cmd.is(menu::REDO) {
+            data.dispatch(Action::Redo);
+            return Handled::Yes;
+        }
+        if cmd.is(menu::SELECT_ALL) {
+            // forward to the focused text box
+            ctx.submit_command(commands::SELECT_ALL.to(target));
+            return Handled::Yes;
+        }
+
+        // view
+        if let Some(filter) = cmd.get(menu::SET_FILTER) {
+            data.filter = filter.clone();
+            return Handled::Yes;
+        }
+        if cmd.is(menu::TOGGLE_MEMO) {
+            data.toggle_memo();
+            return Handled::Yes;
+        }
+        if cmd.is(menu::TOGGLE_BOARD) {
+            data.toggle_board();
+            if data.view == View::Board {
+                // room for a column per status
+                let config = WindowConfig::default().window_size((BOARD_WIDTH, window_height()));
+                ctx.submit_command(commands::CONFIGURE_WINDOW.with(config).to(self.main_window));
+            }
+            return Handled::Yes;
+        }
+        if cmd.is(menu::TOGGLE_STATS) {
+            data.toggle_stats();
+            return Handled::Yes;
+        }
+        if cmd.is(menu::SHOW_POMODORO) {
+            data.show_pomodoro();
+            return Handled::Yes;
+        }
+        if let Some(name) = cmd.get(menu::SET_THEME) {
+            data.settings.theme = *name;
+            data.theme = Theme::load(*name);
+            if let Err(e) = Config::save_settings(&data.settings) {
+                show_message(ctx, &tr("settings-save-failed"), e);
+            }
+            return Handled::Yes;
+        }
+        if let Some(url) = cmd.get(detail::OPEN_LINK) {
+            detail::open_link(url);
+            return Handled::Yes;
+        }
+        if cmd.is(menu::SHOW_SETTINGS) {
+            data.set_view(View::Settings);
+            return Handled::Yes;
+        }
+        if cmd.is(settings::SAVE_SETTINGS) {
+            // a broken config.json is left for the user to fix, the settings screen stays open
+            let config = match Config::try_load() {
+                Ok(config) => Config {
+                    settings: data.settings.clone(),
+                    ..config
+                },
+                Err(e) => {
+                    show_message(ctx, &tr("settings-save-failed"), e);
+                    return Handled::Yes;
+                },
+            };
+            let path = config.data_file();
+            if path != data.repository.filename() {
+                if let Err(e) = self.open_data_file(data, path, config.storage) {
+                    show_message(ctx, &tr("settings-save-failed"), e);
+                    return Handled::Yes;
+                }
+            } else {
+                data.autosave.flush();
+                data.autosave = Autosave::new(data.repository.clone(), data.settings.autosave_delay());
+            }
+            if let Err(e) = Config::save_settings(&data.settings) {
+                show_message(ctx, &tr("settings-save-failed"), e);
+                return Handled::Yes;
+            }
+            if data.view == View::Settings {
+                data.view = View::List;
+            }
+            return Handled::Yes;
+        }
+
+        // sync
+        if cmd.is(sync::SYNC) {
+            data.autosave.flush();
+            let started = data.tasks.to_save_tasks();
+            let repository = data.repository.clone();
+            let sink = self.sink.clone();
+            thread::spawn(move || {
+                let result = repository.sync();
+                let _ = sink.submit_command(sync::SYNC_DONE, (started, result), Target::Auto);
+            });
+            return Handled::Yes;
+        }
+        if let Some((started, result)) = cmd.get(sync::SYNC_DONE) {
+            match result {
+                Ok(merged) => {
+                    // keep the edits made while syncing
+                    data.save_detail();
+                    let combined = merge::merge(started, &data.tasks.to_save_tasks(), &merged.tasks);
+                    data.dispatch(Action::Merge(Tasks::from_save_tasks(combined.tasks.clone())));
+                    data.reload_detail();
+                    if !merged.is_clean() || !combined.is_clean() {
+                        let conflicts = merged.conflicts.iter().chain(combined.conflicts.iter()).map(|conflict| conflict.to_string()).collect::<Vec<String>>();
+                        show_message(ctx, &tr("sync-conflict-title"), format!("{}\n{}", tr("sync-conflict-message"), conflicts.join("\n")));
+                    }
+                },
+                Err(e) => show_message(ctx, &tr("sync-failed-title"), e.to_string()),
+            }
+            return Handled::Yes;
+        }
+
+        // help
+        if cmd.is(menu::ABOUT) {
+            ctx.new_window(
+                WindowDesc::new(configured(make_about()))
+                    .title(tr("about-title"))
+                    .resizable(false)
+                    .window_size((300.0, 140.0)),
+            );
+            return Handled::Yes;
+        }
+
         Handled::No
     }
 }
 
+impl Delegate {
+    /// switch to another data file, the current one is kept if it cannot be opened or another app has it open
+    fn open_data_file(&mut self, data: &mut TaskState, path: String, storage: Storage) -> Result<(), String> {
+        let mut repository = TaskRepository::open(path.clone(), storage)?.keep_backups(data.repository.backups().keep());
+        if repository.lock() == LockStatus::Focused {
+            return Err(tr_args("settings-file-in-use", &[("file", &path)]));
+        }
+        if let Some(lock) = repository.instance_lock() {
+            lock.serve(self.sink.clone(), self.main_window);
+        }
+
+        if data.view == View::Detail {
+            data.close_detail();
+        }
+        data.autosave.flush();
+        if let Some(lock) = data.repository.instance_lock() {
+            lock.release();
+        }
+        data.repository = repository;
+        data.autosave = Autosave::new(data.repository.clone(), data.settings.autosave_delay());
+        self.watcher = Watcher::start(data.repository.clone(), self.sink.clone());
+        data.tasks = Tasks::from_save_tasks(data.repository.load());
+        data.history = History::new();
+        data.conflict = None;
+        if data.repository.is_locked() {
+            data.view = View::Unlock;
+        }
+        Ok(())
+    }
+}
+
+/// apply the current theme and settings to a window, so changes take effect at once
+fn configured(widget: impl Widget<TaskState> + 'static) -> impl Widget<TaskState> {
+    EnvScope::new(
+        |env, data: &TaskState| {
+            data.theme.configure(env);
+            data.settings.configure(env);
+        },
+        widget.background(theme::BACKGROUND),
+    )
+}
+
+/// show a message in a small window
+fn show_message(ctx: &mut DelegateCtx, title: &str, message: String) {
+    ctx.new_window(
+        WindowDesc::new(configured(
+            Label::<TaskState>::new(message)
+                .with_text_size(TASK_TEXT_SIZE)
+                .with_line_break_mode(LineBreaking::WordWrap)
+                .center()
+                .padding(BLOCK_SPACE),
+        ))
+            .title(title.to_string())
+            .resizable(false)
+            .window_size((WINDOW_WIDTH, 160.0)),
+    );
+}
+
+fn make_about() -> impl Widget<TaskState> {
+    Flex::column()
+        .with_child(
+            Label::new("Tasking!")
+                .with_text_size(TEXT_SIZE),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(
+            Label::new(format!("version {}", env!("CARGO_PKG_VERSION")))
+                .with_text_size(TASK_TEXT_SIZE),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(
+            Label::new(tr("about-description"))
+                .with_text_size(TASK_TEXT_SIZE),
+        )
+        .center()
+        .padding(BLOCK_SPACE)
+}
+
 fn make_widget() -> impl Widget<TaskState> {
 
     ViewSwitcher::new(
-        |data: &TaskState, _| data.setting,
-        |flag, _, _| {
-            match flag {
-                true => {
+        // labels are looked up when the view is built
+        |data: &TaskState, _| (data.view, data.conflict.is_some(), data.settings.language),
+        |(view, conflict, _), _, _| {
+            match (*view, *conflict) {
+                (_, true) => {
                     let mut panel = Flex::column();
 
                     panel.add_child(
-                        TextBox::multiline()
-                            .with_text_size(TASK_TEXT_SIZE)
+                        Label::new(tr("conflict-message"))
+                            .with_text_size(TEXT_SIZE)
+                            .with_line_break_mode(LineBreaking::WordWrap)
+                            .expand_width(),
+                    );
+                    panel.add_spacer(BLOCK_SPACE);
+                    panel.add_flex_child(
+                        Scroll::new(
+                            Label::dynamic(|data: &TaskState, _| {
+                                data.conflict.as_ref().map(|conflict| conflict.summary()).unwrap_or_default()
+                            })
+                                .with_text_size(TASK_TEXT_SIZE)
+                                .with_line_break_mode(LineBreaking::WordWrap)
+                                .expand_width(),
+                        )
+                            .vertical(),
+                        1.0,
+                    );
+                    panel.add_spacer(BLOCK_SPACE);
+                    panel.add_child(
+                        make_button::<TaskState>(tr("conflict-merge"), TEXT_SIZE, theme::SUCCESS)
+                            .on_click(|ctx, _, _| {
+                                ctx.submit_command(watch::RESOLVE_CONFLICT.with(Resolution::Merged));
+                            })
                             .expand_width()
-                            .fix_height(WINDOW_HEIGHT - LINE_HEIGHT - BLOCK_SPACE)
-                            .lens(TaskState::memo)
+                            .fix_height(BLOCK_HEIGHT),
                     );
                     panel.add_spacer(BLOCK_SPACE);
                     panel.add_child(
-                        make_button::<TaskState>("戻る".to_string(), TEXT_SIZE,(88, 97, 105))
+                        make_button::<TaskState>(tr("conflict-theirs"), TEXT_SIZE, theme::PRIMARY)
+                            .on_click(|ctx, _, _| {
+                                ctx.submit_command(watch::RESOLVE_CONFLICT.with(Resolution::Theirs));
+                            })
                             .expand_width()
-                            .fix_height(BLOCK_HEIGHT)
+                            .fix_height(BLOCK_HEIGHT),
+                    );
+                    panel.add_spacer(BLOCK_SPACE);
+                    panel.add_child(
+                        make_button::<TaskState>(tr("conflict-mine"), TEXT_SIZE, theme::DARK)
+                            .on_click(|ctx, _, _| {
+                                ctx.submit_command(watch::RESOLVE_CONFLICT.with(Resolution::Mine));
+                            })
+                            .expand_width()
+                            .fix_height(BLOCK_HEIGHT),
+                    );
+
+                    Box::new(
+                        panel
+                            .padding(BLOCK_SPACE),
+                    )
+                },
+                (View::Unlock, _) => Box::new(make_unlock_view()),
+                (View::Backups, _) => Box::new(make_backup_view()),
+                (View::Pomodoro, _) => Box::new(make_pomodoro_view()),
+                (View::Stats, _) => Box::new(make_stats_view()),
+                (View::Board, _) => Box::new(board::make_board_view()),
+                (View::Settings, _) => Box::new(settings::make_settings_view()),
+                (View::Detail, _) => Box::new(detail::make_detail_view()),
+                (View::Memo, _) => {
+                    let mut panel = Flex::column();
+
+                    panel.add_flex_child(
+                        TextBox::multiline()
+                            .with_text_size(TASK_TEXT_SIZE)
+                            .expand()
+                            .lens(TaskState::memo),
+                        1.0,
+                    );
+                    panel.add_spacer(BLOCK_SPACE);
+                    panel.add_child(
+                        make_button::<TaskState>(tr("back"), TEXT_SIZE, theme::DARK)
                             .on_click(|_, data, _| {
-                                data.setting = !data.setting;
-                            }),
+                                data.toggle_memo();
+                            })
+                            .expand_width()
+                            .fix_height(BLOCK_HEIGHT),
                     );
 
                     Box::new(
@@ -133,7 +755,7 @@ fn make_widget() -> impl Widget<TaskState> {
                         Flex::row()
                             .with_flex_child(
                                 TextBox::new()
-                                    .with_placeholder("新しいタスクを入力してください")
+                                    .with_placeholder(tr("new-task-placeholder"))
                                     .with_text_size(TEXT_SIZE)
                                     .expand_width()
                                     .fix_height(BLOCK_HEIGHT)
@@ -142,157 +764,150 @@ fn make_widget() -> impl Widget<TaskState> {
                             )
                             .with_spacer(BLOCK_SPACE)
                             .with_flex_child(
-                                make_button::<TaskState>("追加".to_string(), TEXT_SIZE,(0, 123, 255))
-                                    .expand_width()
-                                    .fix_height(BLOCK_HEIGHT)
+                                make_button::<TaskState>(tr("add"), TEXT_SIZE, theme::PRIMARY)
                                     .on_click(|_, data, _| {
                                         if data.content.len() > 0 {
-                                            data.tasks.add_message(data.content.to_string());
+                                            data.dispatch(Action::Add(data.content.to_string()));
                                             data.content = "".to_string();
-                                            data.repository.save(data.tasks.to_save_tasks());
                                         }
-                                    }),
+                                    })
+                                    .expand_width()
+                                    .fix_height(BLOCK_HEIGHT),
                                 1.0,
                             )
                     );
-                    let mut scroll = Scroll::new(
-                        ViewSwitcher::new(
-                            |data: &TaskState, _| data.tasks.clone(),
-                            |tasks, _, _| {
-                                match tasks.is_empty() {
-                                    true => {
-                                        Box::new(
-                                            Label::new("タスクはまだありません")
-                                                .with_text_size(TEXT_SIZE)
-                                                .center()
-                                                .fix_width(INNER_WIDTH)
-                                                .fix_height(WINDOW_HEIGHT - (LINE_HEIGHT * 2.0))
-                                        )
-                                    },
-                                    _ => {
-                                        Box::new(
-                                            List::new(|| {
-                                                Flex::row()
-                                                    .with_flex_child(
-                                                        Label::new(|(_, item): &(TaskState, Task), _: &Env| item.content.to_string())
-                                                            .with_text_size(TASK_TEXT_SIZE)
-                                                            .padding(5.0)
-                                                            .expand_width(),
-                                                        5.0,
-                                                    )
-                                                    .with_spacer(BLOCK_SPACE)
-                                                    .with_flex_child(
-                                                        make_status_button(|(_, task): &(TaskState, Task), _| task.status.to_string().to_string(), TASK_TEXT_SIZE)
-                                                            .on_click(|_, (tasks, task): &mut (TaskState, Task), _: &Env| {
-                                                                if let Some(t) = tasks.tasks.find_by_id(task.id) {
-                                                                    t.change_status(task.status.next_status());
-                                                                    tasks.repository.save(tasks.tasks.to_save_tasks());
-                                                                }
-                                                            }),
-                                                        1.2,
-                                                    )
-                                                    .with_spacer(BLOCK_SPACE)
-                                                    .with_flex_child(
-                                                        make_button("削除".to_string(), TASK_TEXT_SIZE, (255, 193, 7))
-                                                            .on_click(|_, (tasks, task): &mut (TaskState, Task), _: &Env| {
-                                                                tasks.tasks.remove_by_id(task.id);
-                                                                tasks.repository.save(tasks.tasks.to_save_tasks());
-                                                            }),
-                                                        FlexParams::new(0.8, CrossAxisAlignment::End),
-                                                    )
-                                                    .with_spacer(BLOCK_SPACE)
-                                                    .fix_height(TASK_BLOCK_HEIGHT)
-                                                    .fix_width(INNER_WIDTH)
+                    let list = Either::new(
+                        |data: &TaskState, _| data.tasks.filter(&data.filter).is_empty(),
+                        Label::new(tr("no-tasks"))
+                            .with_text_size(TEXT_SIZE)
+                            .center(),
+                        // the same scroll stays while tasks change, so it keeps its position
+                        Scroll::new(
+                            List::new(|| {
+                                Flex::row()
+                                    .with_flex_child(
+                                        make_text_button(|(_, item): &(TaskState, Task), _: &Env| item.content.to_string(), TASK_TEXT_SIZE, theme::TEXT)
+                                            .with_line_break_mode(LineBreaking::WordWrap)
+                                            .with_align(UnitPoint::LEFT)
+                                            .on_click(|_, (state, task): &mut (TaskState, Task), _: &Env| {
+                                                state.show_detail(&task.id);
                                             })
-                                            .with_spacing(BLOCK_SPACE)
-                                            .lens(lens::Identity.map(
-                                                |d: &TaskState| (d.clone(), d.tasks.tasks.clone()),
-                                                |d: &mut TaskState, (state, _): (TaskState, Vector<Task>)| {
-                                                    d.tasks = state.tasks;
-                                                    d.content = state.content
-                                                },
-                                            ))
+                                            .padding(5.0)
+                                            .expand_width(),
+                                        5.0,
+                                    )
+                                    .with_child(
+                                        // no empty tab stop on tasks without tracked time
+                                        Either::new(
+                                            |(state, task): &(TaskState, Task), _| !tracked_text(state, task).is_empty(),
+                                            make_text_button(|(state, task): &(TaskState, Task), _: &Env| tracked_text(state, task), SMALL_TEXT_SIZE, theme::MUTED)
+                                                .on_click(|_, (state, task): &mut (TaskState, Task), _: &Env| {
+                                                    // running tasks open a pomodoro session
+                                                    state.start_pomodoro(&task.id);
+                                                }),
+                                            SizedBox::empty(),
+                                        ),
+                                    )
+                                    .with_flex_child(
+                                        Either::new(
+                                            |(_, task): &(TaskState, Task), _| task.recurrence.is_some(),
+                                            make_text_button(|(_, task): &(TaskState, Task), _: &Env| {
+                                                task.recurrence.as_ref().map(|recurrence| format!("↻{}", recurrence)).unwrap_or_default()
+                                            }, TASK_TEXT_SIZE, theme::PRIMARY)
+                                                .on_click(cycle_recurrence),
+                                            make_text_button("↻", TASK_TEXT_SIZE, theme::FAINT)
+                                                .on_click(cycle_recurrence),
+                                        ),
+                                        0.9,
+                                    )
+                                    .with_spacer(BLOCK_SPACE)
+                                    .with_flex_child(
+                                        make_status_button(|(_, task): &(TaskState, Task), _| task.status.to_string(), TASK_TEXT_SIZE)
+                                            .on_click(|_, (tasks, task): &mut (TaskState, Task), _: &Env| {
+                                                tasks.dispatch(Action::SetStatus(task.id.clone(), task.status.next_status()));
+                                            })
+                                            .fix_height(TASK_BLOCK_HEIGHT),
+                                        1.2,
+                                    )
+                                    .with_spacer(BLOCK_SPACE)
+                                    .with_flex_child(
+                                        Either::new(
+                                            |(state, task): &(TaskState, Task), _| state.deleting.as_ref() == Some(&task.id),
+                                            make_button(tr("delete-confirm"), TASK_TEXT_SIZE, theme::DANGER)
+                                                .on_click(|_, (tasks, task): &mut (TaskState, Task), _: &Env| {
+                                                    tasks.request_remove(&task.id);
+                                                }),
+                                            make_button(tr("delete"), TASK_TEXT_SIZE, theme::WARNING)
+                                                .on_click(|_, (tasks, task): &mut (TaskState, Task), _: &Env| {
+                                                    tasks.request_remove(&task.id);
+                                                }),
                                         )
-                                    },
-                                }
-                            }
+                                            .fix_height(TASK_BLOCK_HEIGHT),
+                                        FlexParams::new(0.8, CrossAxisAlignment::End),
+                                    )
+                                    .with_spacer(BLOCK_SPACE)
+                            })
+                            .with_spacing(BLOCK_SPACE)
+                            .lens(lens::Identity.map(
+                                |d: &TaskState| (d.clone(), d.settings.sort.apply(d.tasks.filter(&d.filter))),
+                                |d: &mut TaskState, (state, _): (TaskState, Vector<Task>)| {
+                                    d.tasks = state.tasks;
+                                    d.history = state.history;
+                                    d.content = state.content;
+                                    d.now = state.now;
+                                    d.pomodoro = state.pomodoro;
+                                    d.deleting = state.deleting;
+                                    d.detail = state.detail;
+                                    d.view = state.view
+                                },
+                            ))
                         )
+                            .vertical(),
                     );
-                    scroll.set_horizontal_scroll_enabled(false);
 
                     column.add_spacer(BLOCK_SPACE);
-                    column.add_child(
-                        scroll
-                            .fix_width(INNER_WIDTH)
-                            .fix_height(WINDOW_HEIGHT - (LINE_HEIGHT * 2.0))
-                    );
+                    column.add_flex_child(list, 1.0);
 
                     column.add_spacer(10.0);
                     column.add_child(
                         Flex::row()
                             .with_flex_child(
-                                make_button::<TaskState>("クリア".to_string(), TEXT_SIZE,(108, 117, 125))
-                                    .expand_width()
-                                    .fix_height(38.0)
+                                make_button::<TaskState>(tr("clear"), TEXT_SIZE, theme::SECONDARY)
                                     .on_click(|_, data, _| {
-                                        data.tasks.clear();
-                                        data.repository.save(data.tasks.to_save_tasks());
-                                    }),
+                                        data.dispatch(Action::Clear);
+                                    })
+                                    .expand_width()
+                                    .fix_height(38.0),
                                     5.0,
                             )
                             .with_spacer(BLOCK_SPACE)
                             .with_flex_child(
-                                make_button::<TaskState>("整頓".to_string(), TEXT_SIZE,(88, 97, 105))
-                                    .expand_width()
-                                    .fix_height(38.0)
+                                make_button::<TaskState>(tr("sort"), TEXT_SIZE, theme::DARK)
                                     .on_click(|_, data, _| {
-                                        data.tasks.sort(&vec![
-                                            TaskStatus::New,
-                                            TaskStatus::Progress,
-                                            TaskStatus::Stop,
-                                            TaskStatus::Done,
-                                        ]);
-                                        data.repository.save(data.tasks.to_save_tasks());
-                                    }),
+                                        data.dispatch(Action::Sort(TaskStatus::statuses()));
+                                    })
+                                    .expand_width()
+                                    .fix_height(38.0),
                                     1.0,
                             )
                             .with_spacer(BLOCK_SPACE)
                             .with_flex_child(
-                                make_button::<TaskState>("メモ".to_string(), TEXT_SIZE,(88, 97, 105))
+                                make_button::<TaskState>(tr("memo"), TEXT_SIZE, theme::DARK)
+                                    .on_click(|_, data, _| {
+                                        data.toggle_memo();
+                                    })
                                     .expand_width()
-                                    .fix_height(38.0)
+                                    .fix_height(38.0),
+                                    1.0,
+                            )
+                            .with_spacer(BLOCK_SPACE)
+                            .with_flex_child(
+                                make_button::<TaskState>(tr("stats"), TEXT_SIZE, theme::DARK)
                                     .on_click(|_, data, _| {
-                                        let tasks = data.tasks.to_vec();
-                                        let mut text = "".to_string();
-
-                                        for status in vec![
-                                            TaskStatus::New,
-                                            TaskStatus::Progress,
-                                            TaskStatus::Stop,
-                                            TaskStatus::Done,
-                                        ].iter() {
-                                            let mut task_text = format!("# {}タスク\n", status.to_string());
-                                            let mut counter = 0;
-                                            for task in tasks.iter() {
-                                                if task.status.eq(status) {
-                                                    counter += 1;
-                                                    task_text = task_text + format!("{}. {}\n", counter, task.content).as_str();
-                                                }
-                                            }
-
-                                            if counter != 0 {
-                                                text = text + task_text.as_str() + "\n";
-                                            }
-                                        }
-
-                                        text = text.trim().to_string();
-
-                                        if text.len() > 0 {
-                                            data.memo = format!("--- task ---\n{}\n------------", text).to_string();
-                                        }
-
-                                        data.setting = !data.setting;
-                                    }),
+                                        data.toggle_stats();
+                                    })
+                                    .expand_width()
+                                    .fix_height(38.0),
                                     1.0,
                             )
                     );
@@ -306,53 +921,274 @@ fn make_widget() -> impl Widget<TaskState> {
             }
         },
     )
+    .controller(Reminders::new(Box::new(DesktopNotifier)))
+    .controller(Clock::new())
+    .controller(Geometry::new())
 }
 
-fn make_button<T: Data>(label: String, text_size: f64, rgb: (u8, u8, u8)) -> impl Widget<T> {
-    let painter = Painter::new(move |ctx, _, _| {
-        let bounds = ctx.size().to_rounded_rect(BORDER_RADIUS);
+fn make_unlock_view() -> impl Widget<TaskState> {
+    Flex::column()
+        .with_flex_spacer(1.0)
+        .with_child(
+            Label::new(|data: &TaskState, _: &Env| tr_args("unlock-message", &[("file", &data.repository.filename())]))
+                .with_text_size(TASK_TEXT_SIZE)
+                .with_line_break_mode(LineBreaking::WordWrap)
+                .expand_width(),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(
+            widget::password_box(UNLOCK, TEXT_SIZE)
+                .fix_height(BLOCK_HEIGHT)
+                .lens(TaskState::passphrase),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(
+            Label::new(|data: &TaskState, _: &Env| match data.unlock_failed {
+                true => tr("unlock-failed"),
+                false => "".to_string(),
+            })
+                .with_text_size(TASK_TEXT_SIZE)
+                .with_text_color(theme::DANGER),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(
+            make_button::<TaskState>(tr("unlock"), TEXT_SIZE, theme::PRIMARY)
+                .on_click(|ctx, _, _| {
+                    ctx.submit_command(UNLOCK);
+                })
+                .expand_width()
+                .fix_height(BLOCK_HEIGHT),
+        )
+        .with_flex_spacer(1.0)
+        .padding(BLOCK_SPACE)
+}
 
-        let (r, g, b) = rgb;
+fn make_stats_view() -> impl Widget<TaskState> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(
+            Label::new(|data: &TaskState, _: &Env| {
+                Stats::today(&data.tasks).counts.iter()
+                    .map(|(status, count)| tr_args("stats-count", &[("status", &status.to_string()), ("count", count)]))
+                    .collect::<Vec<String>>()
+                    .join("  ")
+            })
+                .with_text_size(TEXT_SIZE),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(
+            Label::new(|data: &TaskState, _: &Env| {
+                let weeks = Stats::today(&data.tasks).weeks.iter()
+                    .map(|(start, count)| tr_args("stats-week", &[("start", &start.format("%m/%d")), ("count", count)]))
+                    .collect::<Vec<String>>()
+                    .join("  ");
+                tr_args("stats-weekly", &[("weeks", &weeks)])
+            })
+                .with_text_size(TASK_TEXT_SIZE)
+                .with_line_break_mode(LineBreaking::WordWrap),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(
+            Label::new(|data: &TaskState, _: &Env| match Stats::today(&data.tasks).average {
+                Some(average) => tr_args("stats-average", &[("average", &stats::format_lead_time(average))]),
+                None => tr_args("stats-average", &[("average", &"-")]),
+            })
+                .with_text_size(TASK_TEXT_SIZE),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(
+            Label::new(tr("stats-daily"))
+                .with_text_size(TASK_TEXT_SIZE)
+                .with_text_color(theme::MUTED),
+        )
+        .with_flex_child(
+            SizedBox::empty()
+                .expand()
+                .background(stats::make_chart()),
+            1.0,
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(
+            make_button::<TaskState>(tr("back"), TEXT_SIZE, theme::DARK)
+                .on_click(|_, data, _| {
+                    data.toggle_stats();
+                })
+                .expand_width()
+                .fix_height(BLOCK_HEIGHT),
+        )
+        .padding(BLOCK_SPACE)
+}
 
-        if ctx.is_active() {
-            ctx.fill(bounds, &Color::rgba8(r, g, b, 180));
-        } else if ctx.is_hot() {
-            ctx.fill(bounds, &Color::rgba8(r, g, b, 210));
-        } else {
-            ctx.fill(bounds, &Color::rgba8(r, g, b, 255));
-        }
+fn make_pomodoro_view() -> impl Widget<TaskState> {
+    let countdown = Painter::new(|ctx, data: &TaskState, env| {
+        let size = ctx.size();
+        let center = Point::new(size.width / 2.0, size.height / 2.0);
+        let radius = size.width.min(size.height) / 2.0 - BLOCK_SPACE;
+        let color = match data.pomodoro.phase {
+            Phase::Work => env.get(theme::DANGER),
+            Phase::Break => env.get(theme::SUCCESS),
+        };
+
+        ctx.stroke(Circle::new(center, radius), &env.get(theme::FAINT), 8.0);
+        // remaining time clockwise from the top
+        let arc = Arc {
+            center: center,
+            radii: Vec2::new(radius, radius),
+            start_angle: -std::f64::consts::FRAC_PI_2,
+            sweep_angle: std::f64::consts::TAU * data.pomodoro.fraction_left(data.now),
+            x_rotation: 0.0,
+        };
+        ctx.stroke(arc, &color, 8.0);
     });
 
-    Label::new(label)
+    Flex::column()
+        .with_child(
+            Label::new(|data: &TaskState, _: &Env| {
+                data.pomodoro.task.as_ref()
+                    .and_then(|id| data.tasks.tasks.iter().find(|task| task.equals(id)))
+                    .map(|task| format!("{}  🍅{}", task.content, task.pomodoros))
+                    .unwrap_or_default()
+            })
+                .with_text_size(TEXT_SIZE)
+                .with_line_break_mode(LineBreaking::WordWrap)
+                .expand_width(),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_flex_child(
+            Flex::column()
+                .with_child(
+                    Label::new(|data: &TaskState, _: &Env| tracking::format_duration(data.pomodoro.remaining(data.now)))
+                        .with_text_size(LARGE_TEXT_SIZE),
+                )
+                .with_child(
+                    Label::new(|data: &TaskState, _: &Env| data.pomodoro.phase.to_string())
+                        .with_text_size(TASK_TEXT_SIZE)
+                        .with_text_color(theme::MUTED),
+                )
+                .center()
+                .background(countdown)
+                .expand(),
+            1.0,
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(
+            Flex::row()
+                .with_flex_child(
+                    make_button::<TaskState>(tr("interrupt"), TEXT_SIZE, theme::DANGER)
+                        .on_click(|_, data, _| {
+                            data.interrupt_pomodoro();
+                        })
+                        .expand_width()
+                        .fix_height(BLOCK_HEIGHT),
+                    1.0,
+                )
+                .with_spacer(BLOCK_SPACE)
+                .with_flex_child(
+                    make_button::<TaskState>(tr("back"), TEXT_SIZE, theme::DARK)
+                        .on_click(|_, data, _| {
+                            data.view = View::List;
+                        })
+                        .expand_width()
+                        .fix_height(BLOCK_HEIGHT),
+                    1.0,
+                ),
+        )
+        .padding(BLOCK_SPACE)
+}
+
+fn make_backup_view() -> impl Widget<TaskState> {
+    let scroll = Scroll::new(
+        List::new(|| {
+            Flex::row()
+                .with_flex_child(
+                    Flex::column()
+                        .cross_axis_alignment(CrossAxisAlignment::Start)
+                        .with_child(
+                            Label::new(|item: &BackupItem, _: &Env| tr_args("backup-count", &[("date", &item.date), ("count", &item.count)]))
+                                .with_text_size(TASK_TEXT_SIZE),
+                        )
+                        .with_child(
+                            Label::new(|item: &BackupItem, _: &Env| item.diff.to_string())
+                                .with_text_size(SMALL_TEXT_SIZE)
+                                .with_text_color(theme::MUTED)
+                                .with_line_break_mode(LineBreaking::WordWrap),
+                        )
+                        .padding(5.0)
+                        .expand_width(),
+                    4.0,
+                )
+                .with_spacer(BLOCK_SPACE)
+                .with_flex_child(
+                    make_button::<BackupItem>(tr("restore"), TASK_TEXT_SIZE, theme::PRIMARY)
+                        .on_click(|ctx, item: &mut BackupItem, _| {
+                            ctx.submit_command(backup::RESTORE_BACKUP.with(item.path.to_string()));
+                        })
+                        .fix_height(TASK_BLOCK_HEIGHT),
+                    1.0,
+                )
+                .with_spacer(BLOCK_SPACE)
+        })
+        .with_spacing(BLOCK_SPACE)
+        .lens(TaskState::backups)
+    )
+        .vertical();
+
+    let mut panel = Flex::column();
+    panel.add_flex_child(
+        Either::new(
+            |data: &TaskState, _| data.backups.is_empty(),
+            Label::new(tr("no-backups"))
+                .with_text_size(TEXT_SIZE)
+                .center(),
+            scroll,
+        ),
+        1.0,
+    );
+    panel.add_spacer(BLOCK_SPACE);
+    panel.add_child(
+        make_button::<TaskState>(tr("back"), TEXT_SIZE, theme::DARK)
+            .on_click(|_, data, _| {
+                data.view = View::List;
+            })
+            .expand_width()
+            .fix_height(BLOCK_HEIGHT),
+    );
+
+    panel.padding(BLOCK_SPACE)
+}
+
+fn make_button<T: Data>(label: String, text_size: Key<f64>, color: Key<Color>) -> Button<T> {
+    Button::new(label, move |_, env| env.get(&color))
         .with_text_size(text_size)
-        .with_text_color(Color::WHITE)
-        .center()
-        .background(painter)
+        .with_text_color(theme::ON_ACCENT)
 }
 
-fn make_status_button(label: fn(&(TaskState, Task), &Env) -> String, text_size: f64) -> impl Widget<(TaskState, Task)> {
-    let painter = Painter::new(move |ctx, (_, task): &(TaskState, Task), _| {
-        let bounds = ctx.size().to_rounded_rect(BORDER_RADIUS);
+// text only, for the clickable parts of a task row
+fn make_text_button<T: Data>(label: impl Into<LabelText<T>>, text_size: Key<f64>, color: Key<Color>) -> Button<T> {
+    Button::new(label, |_, _| Color::TRANSPARENT)
+        .with_text_size(text_size)
+        .with_text_color(color)
+}
 
-        let (r, g, b) = match task.status {
-            TaskStatus::New => (23, 162, 184),
-            TaskStatus::Progress => (40, 167, 69),
-            TaskStatus::Stop => (108, 117, 125),
-            TaskStatus::Done => (255, 0, 0),
-        };
+// tracked time and finished pomodoros of a task row
+fn tracked_text(state: &TaskState, task: &Task) -> String {
+    let mut text = match task.intervals.is_empty() {
+        true => "".to_string(),
+        false => tracking::format_duration(task.tracked(state.now)),
+    };
+    if task.pomodoros > 0 {
+        text = format!("{} 🍅{}", text, task.pomodoros);
+    }
+    text
+}
 
-        if ctx.is_active() {
-            ctx.fill(bounds, &Color::rgba8(r, g, b, 180));
-        } else if ctx.is_hot() {
-            ctx.fill(bounds, &Color::rgba8(r, g, b, 210));
-        } else {
-            ctx.fill(bounds, &Color::rgba8(r, g, b, 255));
-        }
-    });
+// none, daily, weekly, workdays, monthly
+fn cycle_recurrence(_: &mut EventCtx, (tasks, task): &mut (TaskState, Task), _: &Env) {
+    tasks.dispatch(Action::SetRecurrence(task.id.clone(), Recurrence::cycle(&task.recurrence)));
+}
 
-    Label::new(label)
+fn make_status_button(label: fn(&(TaskState, Task), &Env) -> String, text_size: Key<f64>) -> Button<(TaskState, Task)> {
+    Button::new(label, |(_, task): &(TaskState, Task), env| env.get(status_color(&task.status)))
         .with_text_size(text_size)
-        .with_text_color(Color::WHITE)
-        .center()
-        .background(painter)
+        .with_text_color(theme::ON_ACCENT)
 }
@@ -0,0 +1,177 @@
+use druid::{Env, FileInfo, Menu, MenuItem, Selector, SysMods, WindowId};
+
+use crate::i18n::tr;
+use crate::model::TaskStatus;
+use crate::theme::ThemeName;
+use crate::{TaskState, View};
+
+// file menu
+pub const IMPORT: Selector = Selector::new("tasking.file.import");
+pub const EXPORT: Selector = Selector::new("tasking.file.export");
+pub const OPEN_DATA_FILE: Selector = Selector::new("tasking.file.open-data-file");
+pub const SHOW_BACKUPS: Selector = Selector::new("tasking.file.show-backups");
+pub const SHOW_SETTINGS: Selector = Selector::new("tasking.file.show-settings");
+pub const QUIT: Selector = Selector::new("tasking.file.quit");
+
+// file dialog results
+pub const IMPORT_FILE: Selector<FileInfo> = Selector::new("tasking.file.import-file");
+pub const EXPORT_FILE: Selector<FileInfo> = Selector::new("tasking.file.export-file");
+pub const OPEN_DATA_FILE_PATH: Selector<FileInfo> = Selector::new("tasking.file.open-data-file-path");
+
+// edit menu
+pub const UNDO: Selector = Selector::new("tasking.edit.undo");
+pub const REDO: Selector = Selector::new("tasking.edit.redo");
+pub const SELECT_ALL: Selector = Selector::new("tasking.edit.select-all");
+
+// view menu
+pub const SET_FILTER: Selector<Option<TaskStatus>> = Selector::new("tasking.view.set-filter");
+pub const TOGGLE_MEMO: Selector = Selector::new("tasking.view.toggle-memo");
+pub const TOGGLE_BOARD: Selector = Selector::new("tasking.view.toggle-board");
+pub const TOGGLE_STATS: Selector = Selector::new("tasking.view.toggle-stats");
+pub const SHOW_POMODORO: Selector = Selector::new("tasking.view.show-pomodoro");
+pub const SET_THEME: Selector<ThemeName> = Selector::new("tasking.view.set-theme");
+
+// help menu
+pub const ABOUT: Selector = Selector::new("tasking.help.about");
+
+/// application menu bar
+pub fn make_menu(_: Option<WindowId>, _: &TaskState, _: &Env) -> Menu<TaskState> {
+    let menu = Menu::empty();
+
+    // no unused mut on the other platforms
+    #[cfg(target_os = "macos")]
+    let menu = menu.entry(druid::platform_menus::mac::application::default());
+
+    menu
+        .entry(make_file_menu())
+        .entry(make_edit_menu())
+        .entry(make_view_menu())
+        .entry(make_help_menu())
+        // labels are looked up when the menu is built
+        .rebuild_on(|old: &TaskState, data: &TaskState, _| old.settings.language != data.settings.language)
+}
+
+fn make_file_menu() -> Menu<TaskState> {
+    Menu::new(tr("menu-file"))
+        .entry(
+            MenuItem::new(tr("menu-import"))
+                .command(IMPORT)
+                .hotkey(SysMods::Cmd, "i"),
+        )
+        .entry(
+            MenuItem::new(tr("menu-export"))
+                .command(EXPORT)
+                .hotkey(SysMods::Cmd, "e"),
+        )
+        .separator()
+        .entry(
+            MenuItem::new(tr("menu-open"))
+                .command(OPEN_DATA_FILE)
+                .hotkey(SysMods::Cmd, "o"),
+        )
+        .entry(MenuItem::new(tr("menu-backups")).command(SHOW_BACKUPS))
+        .separator()
+        .entry(
+            MenuItem::new(tr("menu-sync"))
+                .command(crate::sync::SYNC)
+                .hotkey(SysMods::Cmd, "s")
+                .enabled_if(|data: &TaskState, _| data.repository.can_sync()),
+        )
+        .separator()
+        .entry(
+            MenuItem::new(tr("menu-settings"))
+                .command(SHOW_SETTINGS)
+                .hotkey(SysMods::Cmd, ","),
+        )
+        .separator()
+        .entry(
+            MenuItem::new(tr("menu-quit"))
+                .command(QUIT)
+                .hotkey(SysMods::Cmd, "q"),
+        )
+}
+
+fn make_edit_menu() -> Menu<TaskState> {
+    Menu::new(tr("menu-edit"))
+        .entry(
+            MenuItem::new(tr("menu-undo"))
+                .command(UNDO)
+                .hotkey(SysMods::Cmd, "z")
+                .enabled_if(|data: &TaskState, _| data.history.can_undo()),
+        )
+        .entry(
+            MenuItem::new(tr("menu-redo"))
+                .command(REDO)
+                .hotkey(SysMods::CmdShift, "Z")
+                .enabled_if(|data: &TaskState, _| data.history.can_redo()),
+        )
+        .separator()
+        .entry(
+            MenuItem::new(tr("menu-select-all"))
+                .command(SELECT_ALL)
+                .hotkey(SysMods::Cmd, "a"),
+        )
+}
+
+fn make_view_menu() -> Menu<TaskState> {
+    let mut filters = Menu::new(tr("menu-filter")).entry(
+        MenuItem::new(tr("menu-all"))
+            .command(SET_FILTER.with(None))
+            .selected_if(|data: &TaskState, _| data.filter.is_none()),
+    );
+
+    for status in TaskStatus::statuses().into_iter() {
+        let selected = status.clone();
+        filters = filters.entry(
+            MenuItem::new(status.to_string())
+                .command(SET_FILTER.with(Some(status)))
+                .selected_if(move |data: &TaskState, _| data.filter == Some(selected.clone())),
+        );
+    }
+
+    let mut themes = Menu::new(tr("menu-theme"));
+    for name in ThemeName::names().into_iter() {
+        themes = themes.entry(
+            MenuItem::new(name.to_string())
+                .command(SET_THEME.with(name))
+                .selected_if(move |data: &TaskState, _| data.theme.name == name),
+        );
+    }
+
+    Menu::new(tr("menu-view"))
+        .entry(filters)
+        .entry(themes)
+        .separator()
+        .entry(
+            MenuItem::new(tr("memo"))
+                .command(TOGGLE_MEMO)
+                .hotkey(SysMods::Cmd, "m")
+                .selected_if(|data: &TaskState, _| data.view == View::Memo),
+        )
+        .entry(
+            MenuItem::new(tr("menu-board"))
+                .command(TOGGLE_BOARD)
+                .hotkey(SysMods::Cmd, "b")
+                .selected_if(|data: &TaskState, _| data.view == View::Board),
+        )
+        .entry(
+            MenuItem::new(tr("stats"))
+                .command(TOGGLE_STATS)
+                .hotkey(SysMods::Cmd, "t")
+                .selected_if(|data: &TaskState, _| data.view == View::Stats),
+        )
+        .entry(
+            MenuItem::new(tr("menu-pomodoro"))
+                .command(SHOW_POMODORO)
+                .hotkey(SysMods::Cmd, "p")
+                .enabled_if(|data: &TaskState, _| {
+                    data.pomodoro.is_active() || data.tasks.tasks.iter().any(|task| task.status == TaskStatus::Progress)
+                })
+                .selected_if(|data: &TaskState, _| data.view == View::Pomodoro),
+        )
+}
+
+fn make_help_menu() -> Menu<TaskState> {
+    Menu::new(tr("menu-help"))
+        .entry(MenuItem::new(tr("about-title")).command(ABOUT))
+}
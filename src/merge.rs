@@ -0,0 +1,323 @@
+use druid::Data;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::i18n::tr_args;
+use crate::model::{Interval, SaveTasks, Task, TaskId};
+
+/// how a task diverged on both sides
+#[derive(Debug, Clone, Copy, PartialEq, Data)]
+pub enum ConflictKind {
+    /// changed differently on both sides
+    Edited,
+    /// removed here, changed on the other side
+    RemovedLocally,
+    /// changed here, removed on the other side
+    RemovedRemotely,
+}
+
+/// a task changed on both sides since the base
+#[derive(Debug, Clone, Data)]
+pub struct Conflict {
+    pub kind: ConflictKind,
+    pub local: Option<Task>,
+    pub remote: Option<Task>,
+}
+
+impl Conflict {
+    /// content to show for the task
+    pub fn content(&self) -> &str {
+        match (&self.local, &self.remote) {
+            (Some(task), _) | (None, Some(task)) => task.content.as_str(),
+            (None, None) => "",
+        }
+    }
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ConflictKind::Edited => write!(f, "{}", tr_args("conflict-edited", &[("content", &self.content())])),
+            ConflictKind::RemovedLocally => write!(f, "{}", tr_args("conflict-removed-locally", &[("content", &self.content())])),
+            ConflictKind::RemovedRemotely => write!(f, "{}", tr_args("conflict-removed-remotely", &[("content", &self.content())])),
+        }
+    }
+}
+
+/// merged tasks and the conflicts left in them
+#[derive(Debug, Clone, Default)]
+pub struct Merge {
+    pub tasks: SaveTasks,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Merge {
+    /// tasks that needed no merging
+    pub fn clean(tasks: SaveTasks) -> Self {
+        Self {
+            tasks: tasks,
+            conflicts: Vec::new(),
+        }
+    }
+
+    /// merged without conflicts
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// three-way merge by task id, conflicting tasks keep the local version
+pub fn merge(base: &SaveTasks, local: &SaveTasks, remote: &SaveTasks) -> Merge {
+    let base_tasks = by_id(base);
+    let local_tasks = by_id(local);
+    let remote_tasks = by_id(remote);
+    let mut merged = Merge::default();
+
+    // local order wins, remote additions go to the end
+    for task in local.tasks.iter() {
+        let original = base_tasks.get(&task.id).copied();
+        match remote_tasks.get(&task.id) {
+            Some(theirs) => match merge_task(original, task, theirs) {
+                Some(task) => merged.tasks.tasks.push(task),
+                None => {
+                    merged.tasks.tasks.push(task.clone());
+                    merged.conflicts.push(Conflict {
+                        kind: ConflictKind::Edited,
+                        local: Some(task.clone()),
+                        remote: Some((*theirs).clone()),
+                    });
+                },
+            },
+            // added here
+            None if original.is_none() => merged.tasks.tasks.push(task.clone()),
+            // removed there and untouched here
+            None if original == Some(task) => (),
+            None => {
+                merged.tasks.tasks.push(task.clone());
+                merged.conflicts.push(Conflict {
+                    kind: ConflictKind::RemovedRemotely,
+                    local: Some(task.clone()),
+                    remote: None,
+                });
+            },
+        }
+    }
+
+    for task in remote.tasks.iter() {
+        if local_tasks.contains_key(&task.id) {
+            continue;
+        }
+
+        match base_tasks.get(&task.id).copied() {
+            // added there
+            None => merged.tasks.tasks.push(task.clone()),
+            // removed here and untouched there
+            Some(original) if original == task => (),
+            Some(_) => merged.conflicts.push(Conflict {
+                kind: ConflictKind::RemovedLocally,
+                local: None,
+                remote: Some(task.clone()),
+            }),
+        }
+    }
+
+    merged
+}
+
+/// merge each field on its own, None when a field changed on both sides
+fn merge_task(base: Option<&Task>, local: &Task, remote: &Task) -> Option<Task> {
+    if local == remote {
+        return Some(local.clone())
+    }
+    // added on both sides with the same id
+    let base = base?;
+
+    let mut task = local.clone();
+    task.content = pick(&base.content, &local.content, &remote.content)?;
+    task.status = pick(&base.status, &local.status, &remote.status)?;
+    task.recurrence = pick(&base.recurrence, &local.recurrence, &remote.recurrence)?;
+    task.due = pick(&base.due, &local.due, &remote.due)?;
+    task.remind_at = pick(&base.remind_at, &local.remind_at, &remote.remind_at)?;
+    task.pomodoros = pick(&base.pomodoros, &local.pomodoros, &remote.pomodoros)?;
+    task.created = pick(&base.created, &local.created, &remote.created)?;
+    task.notes = pick(&base.notes, &local.notes, &remote.notes)?;
+    task.tags = pick(&base.tags, &local.tags, &remote.tags)?;
+    // records only grow, keep those of both sides
+    task.intervals = merge_intervals(&local.intervals, &remote.intervals);
+    task.status_history = union_by(&local.status_history, &remote.status_history, |change| change.at);
+
+    Some(task)
+}
+
+/// the changed side of a field, None if both changed it differently
+fn pick<T: PartialEq + Clone>(base: &T, local: &T, remote: &T) -> Option<T> {
+    if local == remote || remote == base {
+        Some(local.clone())
+    } else if local == base {
+        Some(remote.clone())
+    } else {
+        None
+    }
+}
+
+/// intervals of both sides, one stopped on either side is closed
+fn merge_intervals(local: &[Interval], remote: &[Interval]) -> Vec<Interval> {
+    let mut intervals = union_by(local, remote, |interval| interval.start);
+    for interval in intervals.iter_mut().filter(|interval| interval.end.is_none()) {
+        if let Some(closed) = remote.iter().find(|other| other.start == interval.start && other.end.is_some()) {
+            interval.end = closed.end;
+        }
+    }
+
+    intervals
+}
+
+/// local items and the remote ones missing locally, ordered by key
+fn union_by<T: Clone, K: Ord>(local: &[T], remote: &[T], key: impl Fn(&T) -> K) -> Vec<T> {
+    let mut items = local.to_vec();
+    for item in remote.iter() {
+        if !local.iter().any(|other| key(other) == key(item)) {
+            items.push(item.clone());
+        }
+    }
+    items.sort_by_key(|item| key(item));
+
+    items
+}
+
+fn by_id(tasks: &SaveTasks) -> HashMap<&TaskId, &Task> {
+    tasks.tasks.iter().map(|task| (&task.id, task)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::TaskStatus;
+
+    fn task(content: &str) -> Task {
+        Task::create(TaskId::generate(), content.to_string())
+    }
+
+    fn tasks(tasks: &[&Task]) -> SaveTasks {
+        SaveTasks {
+            tasks: tasks.iter().map(|task| (*task).clone()).collect(),
+        }
+    }
+
+    fn edited(task: &Task, content: &str) -> Task {
+        let mut task = task.clone();
+        task.edit(content.to_string());
+        task
+    }
+
+    #[test]
+    fn keeps_additions_of_both_sides() {
+        let kept = task("kept");
+        let mine = task("mine");
+        let theirs = task("theirs");
+
+        let merged = merge(&tasks(&[&kept]), &tasks(&[&kept, &mine]), &tasks(&[&kept, &theirs]));
+        assert!(merged.is_clean());
+        assert_eq!(merged.tasks.tasks, vec![kept, mine, theirs]);
+    }
+
+    #[test]
+    fn same_id_added_differently_conflicts() {
+        let mine = task("mine");
+        let theirs = edited(&mine, "theirs");
+
+        let merged = merge(&SaveTasks::new(), &tasks(&[&mine]), &tasks(&[&theirs]));
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(merged.conflicts[0].kind, ConflictKind::Edited);
+        assert_eq!(merged.tasks.tasks, vec![mine]);
+    }
+
+    #[test]
+    fn removed_remotely_and_edited_locally_conflicts() {
+        let original = task("original");
+        let mine = edited(&original, "mine");
+
+        let merged = merge(&tasks(&[&original]), &tasks(&[&mine]), &SaveTasks::new());
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(merged.conflicts[0].kind, ConflictKind::RemovedRemotely);
+        assert_eq!(merged.tasks.tasks, vec![mine]);
+    }
+
+    #[test]
+    fn removed_locally_and_edited_remotely_conflicts() {
+        let original = task("original");
+        let theirs = edited(&original, "theirs");
+
+        let merged = merge(&tasks(&[&original]), &SaveTasks::new(), &tasks(&[&theirs]));
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(merged.conflicts[0].kind, ConflictKind::RemovedLocally);
+        assert!(merged.tasks.tasks.is_empty());
+    }
+
+    #[test]
+    fn removal_of_untouched_task_wins() {
+        let original = task("original");
+
+        let merged = merge(&tasks(&[&original]), &tasks(&[&original]), &SaveTasks::new());
+        assert!(merged.is_clean());
+        assert!(merged.tasks.tasks.is_empty());
+    }
+
+    #[test]
+    fn edits_on_both_sides_conflict_and_keep_local() {
+        let original = task("original");
+        let mine = edited(&original, "mine");
+        let theirs = edited(&original, "theirs");
+
+        let merged = merge(&tasks(&[&original]), &tasks(&[&mine]), &tasks(&[&theirs]));
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(merged.conflicts[0].kind, ConflictKind::Edited);
+        assert_eq!(merged.tasks.tasks, vec![mine]);
+    }
+
+    #[test]
+    fn status_changed_on_one_side_is_taken() {
+        let original = task("original");
+        let mut theirs = original.clone();
+        theirs.change_status(TaskStatus::Done);
+        let mine = edited(&original, "mine");
+
+        let merged = merge(&tasks(&[&original]), &tasks(&[&mine]), &tasks(&[&theirs]));
+        assert!(merged.is_clean());
+        let task = &merged.tasks.tasks[0];
+        assert_eq!(task.content, "mine");
+        assert_eq!(task.status, TaskStatus::Done);
+        assert_eq!(task.status_history, theirs.status_history);
+    }
+
+    #[test]
+    fn interval_stopped_remotely_is_closed() {
+        let mut original = task("original");
+        original.change_status(TaskStatus::Progress);
+        let mut theirs = original.clone();
+        theirs.change_status(TaskStatus::Stop);
+
+        let merged = merge(&tasks(&[&original]), &tasks(&[&original]), &tasks(&[&theirs]));
+        assert!(merged.is_clean());
+        let task = &merged.tasks.tasks[0];
+        assert_eq!(task.status, TaskStatus::Stop);
+        assert!(!task.is_running());
+        assert_eq!(task.intervals, theirs.intervals);
+    }
+
+    #[test]
+    fn interval_stopped_remotely_is_closed_after_local_edit() {
+        let mut original = task("original");
+        original.change_status(TaskStatus::Progress);
+        let mine = edited(&original, "mine");
+        let mut theirs = original.clone();
+        theirs.change_status(TaskStatus::Stop);
+
+        let merged = merge(&tasks(&[&original]), &tasks(&[&mine]), &tasks(&[&theirs]));
+        assert!(merged.is_clean());
+        let task = &merged.tasks.tasks[0];
+        assert_eq!(task.content, "mine");
+        assert!(!task.is_running());
+        assert_eq!(task.intervals, theirs.intervals);
+    }
+}
@@ -1,10 +1,20 @@
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, Utc};
 use druid::im::{Vector};
 use druid::{Data};
 use std::fs;
-use std::io::Write;
 use std::path::Path;
-use std::env;
-use serde::{Serialize, Deserialize};
+use std::sync::{Arc, Mutex, MutexGuard};
+use serde::{Serialize, Deserialize, Deserializer};
+use std::fmt;
+use ulid::Ulid;
+
+use crate::backup::{BackupItem, Backups};
+use crate::config::{Storage, SyncConfig};
+use crate::i18n::{self, tr, tr_args, Locale};
+use crate::lock::{InstanceLock, LockStatus};
+use crate::merge::{self, Merge};
+use crate::store::{self, Change, TaskStore};
+use crate::sync::GitSync;
 
 #[derive(Serialize, Deserialize, Debug, Clone, Data)]
 pub enum TaskStatus {
@@ -15,15 +25,41 @@ pub enum TaskStatus {
 }
 
 impl TaskStatus {
-    pub fn to_string(&self) -> &str {
+    fn key(&self) -> &str {
         match self {
-            Self::New => "新規",
-            Self::Progress => "実行中",
-            Self::Stop => "停止",
-            Self::Done => "完了",
+            Self::New => "status-new",
+            Self::Progress => "status-progress",
+            Self::Stop => "status-stop",
+            Self::Done => "status-done",
         }
     }
 
+    /// all statuses in display order
+    pub fn statuses() -> Vec<Self> {
+        vec![
+            Self::New,
+            Self::Progress,
+            Self::Stop,
+            Self::Done,
+        ]
+    }
+
+    /// find status by export heading, written in any language
+    pub fn from_heading(heading: &str) -> Option<Self> {
+        Self::statuses().into_iter().find(|status| {
+            Locale::locales().into_iter().any(|locale| heading == status.heading_in(locale))
+        })
+    }
+
+    /// export heading in the current language
+    pub fn heading(&self) -> String {
+        self.heading_in(i18n::locale())
+    }
+
+    fn heading_in(&self, locale: Locale) -> String {
+        i18n::tr_args_in(locale, "export-heading", &[("status", &i18n::tr_in(locale, self.key()))])
+    }
+
     pub fn next_status(&self) -> Self {
         match self {
             Self::New => Self::Progress,
@@ -34,55 +70,339 @@ impl TaskStatus {
     }
 }
 
+impl fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", tr(self.key()))
+    }
+}
+
 impl PartialEq for TaskStatus {
     fn eq(&self, other: &Self) -> bool {
         format!("{:?}", self) == format!("{:?}", other)
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Data)]
+/// globally unique task id
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash, Data)]
+pub struct TaskId(String);
+
+impl TaskId {
+    /// new unique id
+    pub fn generate() -> Self {
+        Self(Ulid::new().to_string())
+    }
+}
+
+impl From<&str> for TaskId {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+impl fmt::Display for TaskId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for TaskId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // files before unique ids used a numeric counter
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawId {
+            Text(String),
+            Number(u64),
+        }
+
+        Ok(match RawId::deserialize(deserializer)? {
+            RawId::Text(id) => Self(id),
+            RawId::Number(id) => Self(id.to_string()),
+        })
+    }
+}
+
+/// repeat rule of a task
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Data)]
+pub enum Recurrence {
+    Daily,
+    Weekly,
+    // bit 0 is monday
+    Weekdays(u8),
+    Monthly,
+}
+
+impl Recurrence {
+    /// monday to friday
+    pub const WORKDAYS: u8 = 0b0011111;
+
+    const DAY_NAMES: [&'static str; 7] = ["day-mon", "day-tue", "day-wed", "day-thu", "day-fri", "day-sat", "day-sun"];
+
+    /// next rule offered in the task row, None stops repeating
+    pub fn cycle(current: &Option<Self>) -> Option<Self> {
+        match current {
+            None => Some(Self::Daily),
+            Some(Self::Daily) => Some(Self::Weekly),
+            Some(Self::Weekly) => Some(Self::Weekdays(Self::WORKDAYS)),
+            Some(Self::Weekdays(_)) => Some(Self::Monthly),
+            Some(Self::Monthly) => None,
+        }
+    }
+
+    /// weekday rule with day switched, None when no day is left
+    pub fn toggle_day(current: &Option<Self>, day: usize) -> Option<Self> {
+        let days = match current {
+            Some(Self::Weekdays(days)) => *days,
+            _ => 0,
+        };
+        match days ^ (1 << day) {
+            0 => None,
+            days => Some(Self::Weekdays(days)),
+        }
+    }
+
+    /// repeats on day, 0 is monday
+    pub fn has_day(current: &Option<Self>, day: usize) -> bool {
+        matches!(current, Some(Self::Weekdays(days)) if days & (1 << day) != 0)
+    }
+
+    /// short name of a weekday, 0 is monday
+    pub fn day_name(day: usize) -> String {
+        tr(Self::DAY_NAMES[day])
+    }
+
+    /// occurrence after date
+    pub fn next(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Daily => date + Duration::days(1),
+            Self::Weekly => date + Duration::days(7),
+            Self::Weekdays(days) => (1..=7)
+                .map(|offset| date + Duration::days(offset))
+                .find(|next| days & (1 << next.weekday().num_days_from_monday()) != 0)
+                .unwrap_or(date + Duration::days(7)),
+            // short months end on their last day
+            Self::Monthly => date.checked_add_months(Months::new(1)).unwrap_or(date + Duration::days(30)),
+        }
+    }
+
+    /// first occurrence after date that is still ahead of today
+    pub fn following(&self, date: NaiveDate, today: NaiveDate) -> NaiveDate {
+        let mut next = self.next(date);
+        while next <= today {
+            next = self.next(next);
+        }
+
+        next
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::Daily => tr("recurrence-daily"),
+            Self::Weekly => tr("recurrence-weekly"),
+            Self::Weekdays(Self::WORKDAYS) => tr("recurrence-workdays"),
+            Self::Weekdays(days) => {
+                let names: Vec<String> = (0..7).filter(|day| days & (1 << day) != 0).map(|day| tr(Self::DAY_NAMES[day])).collect();
+                tr_args("recurrence-days", &[("days", &names.join(&tr("recurrence-separator")))])
+            },
+            Self::Monthly => tr("recurrence-monthly"),
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// time spent in progress, open while running
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Interval {
+    pub start: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl Interval {
+    /// length up to now if still open
+    pub fn duration(&self, now: DateTime<Utc>) -> Duration {
+        self.end.unwrap_or(now) - self.start
+    }
+}
+
+/// status a task entered and when
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StatusChange {
+    pub status: TaskStatus,
+    pub at: DateTime<Utc>,
+}
+
+// hour to remind tasks with only a due date
+const DUE_REMINDER_HOUR: u32 = 9;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Data)]
 pub struct Task {
-    pub id: u32,
+    pub id: TaskId,
     pub content: String,
     pub status: TaskStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[data(eq)]
+    pub due: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[data(eq)]
+    pub remind_at: Option<NaiveDateTime>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[data(eq)]
+    pub intervals: Vec<Interval>,
+    // finished pomodoro work phases
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub pomodoros: u32,
+    // unknown for tasks saved by older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[data(eq)]
+    pub created: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[data(eq)]
+    pub status_history: Vec<StatusChange>,
+    // multi-line markdown notes
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[data(eq)]
+    pub tags: Vec<String>,
+}
+
+fn is_zero(count: &u32) -> bool {
+    *count == 0
 }
 
 impl Task {
     /// create task
-    pub fn create(id: u32, content: String) -> Self {
+    pub fn create(id: TaskId, content: String) -> Self {
         Self {
             id: id,
             content: content,
             status: TaskStatus::New,
+            recurrence: None,
+            due: None,
+            remind_at: None,
+            intervals: Vec::new(),
+            pomodoros: 0,
+            created: Some(Utc::now()),
+            status_history: Vec::new(),
+            notes: String::new(),
+            tags: Vec::new(),
         }
     }
 
-    /// change task status
+    /// explicit reminder, otherwise the morning of the due date
+    pub fn reminder_time(&self) -> Option<NaiveDateTime> {
+        self.remind_at.or_else(|| self.due.and_then(|due| due.and_hms_opt(DUE_REMINDER_HOUR, 0, 0)))
+    }
+
+    /// repeat the task, the first occurrence is due today
+    pub fn set_recurrence(&mut self, recurrence: Option<Recurrence>) {
+        if recurrence.is_some() && self.due.is_none() {
+            self.due = Some(Local::now().date_naive());
+        }
+        self.recurrence = recurrence;
+    }
+
+    /// change task status, entering and leaving progress tracks time
     pub fn change_status(&mut self, status: TaskStatus) {
+        let now = Utc::now();
+        if status == TaskStatus::Progress && !self.is_running() {
+            self.intervals.push(Interval {
+                start: now,
+                end: None,
+            });
+        }
+        if status != TaskStatus::Progress {
+            for interval in self.intervals.iter_mut().filter(|interval| interval.end.is_none()) {
+                interval.end = Some(now);
+            }
+        }
+        if status != self.status {
+            self.status_history.push(StatusChange {
+                status: status.clone(),
+                at: now,
+            });
+        }
         self.status = status;
     }
 
+    /// when the task was last completed
+    pub fn completed_at(&self) -> Option<DateTime<Utc>> {
+        if self.status != TaskStatus::Done {
+            return None
+        }
+        self.status_history.iter().rev().find(|change| change.status == TaskStatus::Done).map(|change| change.at)
+    }
+
+    /// time is being tracked
+    pub fn is_running(&self) -> bool {
+        self.intervals.iter().any(|interval| interval.end.is_none())
+    }
+
+    /// total tracked time
+    pub fn tracked(&self, now: DateTime<Utc>) -> Duration {
+        self.intervals.iter().fold(Duration::zero(), |total, interval| total + interval.duration(now))
+    }
+
+    /// edit task content
+    pub fn edit(&mut self, content: String) {
+        self.content = content;
+    }
+
     /// task equals
-    pub fn equals(&self, id: u32) -> bool {
-        self.id == id
+    pub fn equals(&self, id: &TaskId) -> bool {
+        self.id == *id
     }
 }
 
 /// save task collection
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
 pub struct SaveTasks {
-    pub id_counter: u32,
     pub tasks: Vec<Task>,
 }
 
 impl SaveTasks {
     pub fn new() -> Self {
         Self {
-            id_counter: 0,
             tasks: Vec::new(),
         }
     }
 
+    /// parse exported memo text
+    pub fn from_text(text: &str) -> Self {
+        let mut tasks = Self::new();
+        let mut status = TaskStatus::New;
+
+        for line in text.lines().map(|line| line.trim()) {
+            if let Some(heading) = line.strip_prefix("# ") {
+                if let Some(s) = TaskStatus::from_heading(heading) {
+                    status = s;
+                }
+                continue;
+            }
+
+            if line.is_empty() || line.starts_with("---") {
+                continue;
+            }
+
+            // strip list number
+            let content = match line.split_once(". ") {
+                Some((number, content)) if number.parse::<u32>().is_ok() => content,
+                _ => line,
+            };
+
+            let mut task = Task::create(TaskId::generate(), content.to_string());
+            task.status = status.clone();
+            tasks.tasks.push(task);
+        }
+
+        tasks
+    }
+
     /// convert vec to vector
     pub fn to_vector(&self) -> Vector<Task> {
         let mut vec: Vector<Task> = Vector::new();
@@ -97,7 +417,6 @@ impl SaveTasks {
 /// task collection
 #[derive(Default, Debug, Clone, Data)]
 pub struct Tasks {
-    pub id_counter: u32,
     pub tasks: Vector<Task>,
 }
 
@@ -105,34 +424,121 @@ impl Tasks {
     /// construct
     pub fn new() -> Self {
         Self {
-            id_counter: 0,
             tasks: Vector::new(),
         }
     }
 
     pub fn from_save_tasks(tasks: SaveTasks) -> Self {
         Self {
-            id_counter: tasks.id_counter,
             tasks: tasks.to_vector(),
         }
     }
 
     /// find task by id
-    pub fn find_by_id(&mut self, id: u32) -> Option<&mut Task> {
+    pub fn find_by_id(&mut self, id: &TaskId) -> Option<&mut Task> {
         self.tasks.iter_mut().find(|task| task.equals(id))
     }
 
     /// add task message
     pub fn add_message(&mut self, message: String) {
-        let id = self.generate_task_id();
-        self.tasks.push_back(Task::create(id , message));
+        self.tasks.push_back(Task::create(TaskId::generate(), message));
+    }
+
+    /// import tasks with new ids
+    pub fn import(&mut self, tasks: Vec<Task>) {
+        for task in tasks.into_iter() {
+            self.tasks.push_back(Task {
+                id: TaskId::generate(),
+                ..task
+            });
+        }
+    }
+
+    /// add the next occurrence of a recurring task and return it
+    pub fn repeat_by_id(&mut self, id: &TaskId) -> Option<Task> {
+        let task = self.tasks.iter().find(|task| task.equals(id))?.clone();
+        let recurrence = task.recurrence.clone()?;
+
+        // completed again after reopening, the next one is already there
+        let pending = self.tasks.iter().any(|other| {
+            !other.equals(id) && other.content == task.content && other.recurrence == task.recurrence && other.status != TaskStatus::Done
+        });
+        if pending {
+            return None
+        }
+
+        let today = Local::now().date_naive();
+        let due = recurrence.following(task.due.unwrap_or(today), today);
+
+        self.add_message(task.content);
+        let next = self.tasks.back_mut()?;
+        next.recurrence = Some(recurrence);
+        next.due = Some(due);
+
+        Some(next.clone())
+    }
+
+    /// set or stop the recurrence of a task
+    pub fn set_recurrence_by_id(&mut self, id: &TaskId, recurrence: Option<Recurrence>) {
+        if let Some(task) = self.find_by_id(id) {
+            task.set_recurrence(recurrence);
+        }
+    }
+
+    /// count a finished pomodoro on a task
+    pub fn count_pomodoro_by_id(&mut self, id: &TaskId) {
+        if let Some(task) = self.find_by_id(id) {
+            task.pomodoros += 1;
+        }
+    }
+
+    /// set or clear the due date of a task
+    pub fn set_due_by_id(&mut self, id: &TaskId, due: Option<NaiveDate>) {
+        if let Some(task) = self.find_by_id(id) {
+            task.due = due;
+        }
+    }
+
+    /// replace the notes of a task
+    pub fn set_notes_by_id(&mut self, id: &TaskId, notes: String) {
+        if let Some(task) = self.find_by_id(id) {
+            task.notes = notes;
+        }
+    }
+
+    /// replace the tags of a task
+    pub fn set_tags_by_id(&mut self, id: &TaskId, tags: Vec<String>) {
+        if let Some(task) = self.find_by_id(id) {
+            task.tags = tags;
+        }
+    }
+
+    /// set or clear the reminder time of a task
+    pub fn set_reminder_by_id(&mut self, id: &TaskId, remind_at: Option<NaiveDateTime>) {
+        if let Some(task) = self.find_by_id(id) {
+            task.remind_at = remind_at;
+        }
     }
 
     /// remove task by id
-    pub fn remove_by_id(&mut self, id: u32) {
+    pub fn remove_by_id(&mut self, id: &TaskId) {
         self.tasks.retain(|task| !task.equals(id));
     }
 
+    /// edit task content by id
+    pub fn edit_by_id(&mut self, id: &TaskId, content: String) {
+        if let Some(task) = self.find_by_id(id) {
+            task.edit(content);
+        }
+    }
+
+    /// change task status by id
+    pub fn change_status_by_id(&mut self, id: &TaskId, status: TaskStatus) {
+        if let Some(task) = self.find_by_id(id) {
+            task.change_status(status);
+        }
+    }
+
     /// clear all tasks
     pub fn clear(&mut self) {
         self.tasks.retain(|_| false);
@@ -157,6 +563,36 @@ impl Tasks {
         }
     }
 
+    /// filter tasks by status
+    pub fn filter(&self, status: &Option<TaskStatus>) -> Vector<Task> {
+        match status {
+            Some(status) => self.tasks.iter().filter(|task| task.status.eq(status)).cloned().collect(),
+            None => self.tasks.clone(),
+        }
+    }
+
+    /// convert tasks to memo text
+    pub fn to_text(&self) -> String {
+        let mut text = "".to_string();
+
+        for status in TaskStatus::statuses().iter() {
+            let mut task_text = format!("# {}\n", status.heading());
+            let mut counter = 0;
+            for task in self.tasks.iter() {
+                if task.status.eq(status) {
+                    counter += 1;
+                    task_text = task_text + format!("{}. {}\n", counter, task.content).as_str();
+                }
+            }
+
+            if counter != 0 {
+                text = text + task_text.as_str() + "\n";
+            }
+        }
+
+        text.trim().to_string()
+    }
+
     /// is empty
     pub fn is_empty(&self) -> bool {
         self.tasks.is_empty()
@@ -175,61 +611,334 @@ impl Tasks {
     /// convert tasks to save_tasks
     pub fn to_save_tasks(&self) -> SaveTasks {
         return SaveTasks {
-            id_counter: self.id_counter,
             tasks: self.to_vec(),
         }
     }
+}
 
-    /// generate task_id
-    fn generate_task_id(&mut self) -> u32 {
-        self.id_counter += 1;
-        self.id_counter
+/// undo/redo history
+#[derive(Default, Debug, Clone, Data)]
+pub struct History {
+    undo: Vector<Tasks>,
+    redo: Vector<Tasks>,
+}
+
+impl History {
+    const LIMIT: usize = 100;
+
+    /// construct
+    pub fn new() -> Self {
+        Self {
+            undo: Vector::new(),
+            redo: Vector::new(),
+        }
+    }
+
+    /// record tasks before a change
+    pub fn record(&mut self, tasks: &Tasks) {
+        self.undo.push_back(tasks.clone());
+        if self.undo.len() > Self::LIMIT {
+            self.undo.pop_front();
+        }
+        self.redo.clear();
+    }
+
+    /// step back, returns previous tasks
+    pub fn undo(&mut self, current: &Tasks) -> Option<Tasks> {
+        let tasks = self.undo.pop_back()?;
+        self.redo.push_back(current.clone());
+        Some(tasks)
+    }
+
+    /// step forward, returns next tasks
+    pub fn redo(&mut self, current: &Tasks) -> Option<Tasks> {
+        let tasks = self.redo.pop_back()?;
+        self.undo.push_back(current.clone());
+        Some(tasks)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
     }
 }
 
-/// task json repository
-#[derive(Default, Data, Clone)]
+// default number of dated backups
+const BACKUP_COUNT: usize = 7;
+
+/// task repository over a task store
+#[derive(Data, Clone)]
 pub struct TaskRepository {
     filename: String,
+    #[data(eq)]
+    storage: Storage,
+    store: Arc<Mutex<Box<dyn TaskStore>>>,
+    // tasks as last read or written by this repository
+    synced: Arc<Mutex<String>>,
+    lock: Option<Arc<InstanceLock>>,
+    read_only: bool,
+    #[data(ignore)]
+    backups: Backups,
+    git: Option<Arc<GitSync>>,
 }
 
 impl TaskRepository {
-    /// construct
-    pub fn new(filename: String) -> Self {
-
-        let path_buf = env::current_exe().expect("カレントパスを取得できませんでした");
-        println!("{}", path_buf.display());
-        if let Some(path) = path_buf.parent() {
-            let pb = path.join(filename.clone());
-            if let Some(p) = pb.to_str() {
-                println!("{}", p.to_string());
-                return Self {
-                    filename: p.to_string(),
-                }
-            }
+    /// construct with path as is, fails if the file cannot be opened with the storage
+    pub fn open(filename: String, storage: Storage) -> Result<Self, String> {
+        Ok(Self {
+            store: Arc::new(Mutex::new(store::open(filename.as_str(), storage)?)),
+            backups: Backups::new(filename.as_str(), BACKUP_COUNT),
+            filename: filename,
+            storage: storage,
+            synced: Arc::new(Mutex::new(String::new())),
+            lock: None,
+            read_only: false,
+            git: None,
+        })
+    }
+
+    /// commit every save to git and sync with the remote on demand
+    pub fn with_sync(mut self, config: &SyncConfig) -> Self {
+        if self.storage != Storage::Json {
+            eprintln!("同期を開始できませんでした: {}", tr("sync-unsupported"));
+            return self
+        }
+        match GitSync::open(self.filename.as_str(), config) {
+            Ok(git) => self.git = Some(Arc::new(git)),
+            Err(e) => eprintln!("同期を開始できませんでした: {}", e),
+        }
+        self
+    }
+
+    /// git sync is configured
+    pub fn can_sync(&self) -> bool {
+        self.git.is_some()
+    }
+
+    /// pull, merge by task and push, returns the merged tasks
+    pub fn sync(&self) -> Result<Merge, String> {
+        // only json diffs and merges line by line in git
+        if self.storage != Storage::Json {
+            return Err(tr("sync-unsupported"))
+        }
+        let git = self.git.as_ref().ok_or(tr("sync-not-configured"))?;
+        if self.is_locked() {
+            return Err(tr("sync-locked"))
         }
 
-        Self {
-            filename: filename,
+        // the store stays free for saves while git talks to the remote
+        let fetched = git.fetch()?;
+        let merged = {
+            let mut store = self.store();
+            match fetched {
+                false => Merge::clean(store.load().unwrap_or_default()),
+                true if git.is_up_to_date() => Merge::clean(store.load().unwrap_or_default()),
+                true if git.can_fast_forward() => {
+                    git.fast_forward()?;
+                    Merge::clean(store.load().unwrap_or_default())
+                },
+                true => {
+                    let theirs = git.remote_contents()
+                        .and_then(|serialized| store.decode(&serialized))
+                        .ok_or(tr("sync-remote-unreadable"))?;
+                    // unrelated histories merge from nothing
+                    let base = git.base_contents()
+                        .and_then(|serialized| store.decode(&serialized))
+                        .unwrap_or_default();
+                    let ours = store.load().unwrap_or_default();
+                    let merged = merge::merge(&base, &ours, &theirs);
+
+                    git.begin_merge()?;
+                    store.save(&merged.tasks);
+                    let message = match merged.is_clean() {
+                        true => tr("sync-merge-message"),
+                        false => tr_args("sync-merge-conflicts-message", &[("count", &merged.conflicts.len())]),
+                    };
+                    git.finish_merge(message.as_str())?;
+                    merged
+                },
+            }
+        };
+        git.push()?;
+
+        self.mark_synced(&merged.tasks);
+        Ok(merged)
+    }
+
+    /// keep count dated backups, 0 disables them
+    pub fn keep_backups(mut self, count: usize) -> Self {
+        self.backups = Backups::new(self.filename.as_str(), count);
+        self
+    }
+
+    /// dated backups of the data file
+    pub fn backups(&self) -> &Backups {
+        &self.backups
+    }
+
+    /// backup rows compared with tasks
+    pub fn backup_items(&self, tasks: &SaveTasks) -> Vec<BackupItem> {
+        let store = self.store();
+        self.backups.items(tasks, |serialized| store.decode(serialized))
+    }
+
+    /// load tasks from a backup file
+    pub fn load_backup(&self, path: &str) -> Option<SaveTasks> {
+        let serialized = fs::read_to_string(Path::new(path)).ok()?;
+        self.store().decode(&serialized)
+    }
+
+    /// waiting for a passphrase
+    pub fn is_locked(&self) -> bool {
+        self.store().is_locked()
+    }
+
+    /// unlock an encrypted data file
+    pub fn unlock(&self, passphrase: &str) -> bool {
+        self.store().unlock(passphrase)
+    }
+
+    /// lock the data file against other instances
+    pub fn lock(&mut self) -> LockStatus {
+        match InstanceLock::acquire(self.filename.as_str()) {
+            Ok(lock) => {
+                self.lock = Some(Arc::new(lock));
+                LockStatus::Locked
+            },
+            Err(status) => {
+                self.read_only = status == LockStatus::ReadOnly;
+                status
+            },
         }
     }
 
+    /// instance lock held by this repository
+    pub fn instance_lock(&self) -> Option<&InstanceLock> {
+        self.lock.as_deref()
+    }
+
+    /// another instance may be writing the data file
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// data file path
+    pub fn filename(&self) -> &str {
+        self.filename.as_str()
+    }
+
     /// save tasks
     pub fn save(&self, tasks: SaveTasks) {
-        let path = Path::new(self.filename.as_str());
-        let serialized = serde_json::to_string(&tasks).expect("シリアライズできませんでした");
-        let mut file = fs::File::create(path).expect(format!("{} ファイルが開けませんでした", path.display()).as_str());
-        writeln!(file, "{}", serialized).expect("ファイルに書き込めませんでした");
+        self.apply(&[Change::Replace], tasks);
     }
 
-    /// load tasks from json
-    pub fn load(&self) -> SaveTasks {
-        if let Ok(serialized) = fs::read_to_string(Path::new(self.filename.as_str())) {
-            if let Ok(tasks) = serde_json::from_str::<SaveTasks>(&serialized) {
-                return tasks
+    /// save changes, tasks is the state after all of them
+    pub fn apply(&self, changes: &[Change], tasks: SaveTasks) {
+        let mut store = self.store();
+        if self.read_only || store.is_locked() {
+            return
+        }
+
+        // keep the stored state from before the first save of the day
+        if !self.backups.has_today() {
+            if let Some(current) = store.load() {
+                self.backups.write_today(&store.encode(&current));
             }
         }
 
-        SaveTasks::new()
+        store.apply(changes, &tasks);
+        if let Some(git) = &self.git {
+            let message = changes.iter().map(|change| change.to_string()).collect::<Vec<String>>().join("\n");
+            git.commit(message.as_str());
+        }
+        self.mark_synced(&tasks);
+    }
+
+    /// load tasks
+    pub fn load(&self) -> SaveTasks {
+        match self.store().load() {
+            Some(tasks) => {
+                self.mark_synced(&tasks);
+                tasks
+            },
+            None => SaveTasks::new(),
+        }
+    }
+
+    /// load tasks if the file was changed by someone else
+    pub fn read_changed(&self) -> Option<SaveTasks> {
+        let tasks = self.store().load()?;
+        match self.is_synced(&tasks) {
+            true => None,
+            false => Some(tasks),
+        }
+    }
+
+    /// tasks equal to the file contents last seen
+    pub fn is_synced(&self, tasks: &SaveTasks) -> bool {
+        let serialized = serde_json::to_string(tasks).expect("シリアライズできませんでした");
+        serialized == self.synced()
+    }
+
+    /// file contents last seen, the base for merging external changes
+    pub fn synced_tasks(&self) -> SaveTasks {
+        serde_json::from_str(&self.synced()).unwrap_or_default()
+    }
+
+    /// accept tasks as the current file contents
+    pub fn mark_synced(&self, tasks: &SaveTasks) {
+        self.set_synced(serde_json::to_string(tasks).expect("シリアライズできませんでした"));
+    }
+
+    // a writer that panicked leaves the store usable for the next save
+    fn store(&self) -> MutexGuard<Box<dyn TaskStore>> {
+        self.store.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn synced(&self) -> String {
+        self.synced.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn set_synced(&self, serialized: String) {
+        *self.synced.lock().unwrap_or_else(|e| e.into_inner()) = serialized;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn loads_files_with_numeric_ids() {
+        let path = env::temp_dir().join(format!("tasking-numeric-{}.json", TaskId::generate()));
+        let filename = path.to_string_lossy().to_string();
+        fs::write(&path, r#"{"id_counter":2,"tasks":[{"id":1,"content":"first","status":"Done"},{"id":2,"content":"second","status":"New"}]}"#)
+            .expect("write");
+
+        let repository = TaskRepository::open(filename, Storage::Json).expect("repository").keep_backups(0);
+        let mut tasks = Tasks::from_save_tasks(repository.load());
+        assert_eq!(tasks.tasks.len(), 2);
+        assert_eq!(tasks.tasks[0].id, TaskId::from("1"));
+        assert_eq!(tasks.tasks[0].status, TaskStatus::Done);
+        assert_eq!(tasks.tasks[1].id, TaskId::from("2"));
+        assert_eq!(tasks.tasks[1].content, "second");
+
+        // new tasks never collide with the old numbers and ids are saved as text
+        tasks.add_message("third".to_string());
+        assert!(tasks.find_by_id(&TaskId::from("1")).is_some());
+        repository.save(tasks.to_save_tasks());
+        let saved = fs::read_to_string(&path).expect("read");
+        assert!(saved.contains(r#""id":"1""#));
+
+        let reloaded = repository.load();
+        assert_eq!(reloaded.tasks.len(), 3);
+        assert_eq!(reloaded.tasks[2].content, "third");
+
+        fs::remove_file(&path).expect("remove");
     }
 }
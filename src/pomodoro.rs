@@ -0,0 +1,163 @@
+use chrono::{DateTime, Duration, Utc};
+use druid::Data;
+use std::fmt;
+
+use crate::action::Action;
+use crate::i18n::tr;
+use crate::model::{TaskId, TaskStatus};
+use crate::{TaskState, View};
+
+/// part of a pomodoro cycle
+#[derive(Debug, Clone, Copy, PartialEq, Data)]
+pub enum Phase {
+    Work,
+    Break,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::Work => tr("phase-work"),
+            Self::Break => tr("phase-break"),
+        };
+        write!(f, "{}", label)
+    }
+}
+
+// phases last a minute to a day
+const MIN_MINUTES: i64 = 1;
+const MAX_MINUTES: i64 = 24 * 60;
+
+/// pomodoro timer attached to a running task
+#[derive(Debug, Clone, Data)]
+pub struct Pomodoro {
+    pub task: Option<TaskId>,
+    pub phase: Phase,
+    #[data(eq)]
+    started: DateTime<Utc>,
+    work_minutes: i64,
+    break_minutes: i64,
+}
+
+impl Pomodoro {
+    /// construct with phase lengths in minutes
+    pub fn new(work_minutes: i64, break_minutes: i64) -> Self {
+        Self {
+            task: None,
+            phase: Phase::Work,
+            started: Utc::now(),
+            work_minutes: work_minutes.clamp(MIN_MINUTES, MAX_MINUTES),
+            break_minutes: break_minutes.clamp(MIN_MINUTES, MAX_MINUTES),
+        }
+    }
+
+    /// a session is attached to a task
+    pub fn is_active(&self) -> bool {
+        self.task.is_some()
+    }
+
+    /// time left in the current phase
+    pub fn remaining(&self, now: DateTime<Utc>) -> Duration {
+        (self.started + self.length() - now).max(Duration::zero())
+    }
+
+    /// share of the current phase still left, from 1 to 0
+    pub fn fraction_left(&self, now: DateTime<Utc>) -> f64 {
+        self.remaining(now).num_seconds() as f64 / self.length().num_seconds() as f64
+    }
+
+    fn length(&self) -> Duration {
+        match self.phase {
+            Phase::Work => Duration::minutes(self.work_minutes),
+            Phase::Break => Duration::minutes(self.break_minutes),
+        }
+    }
+
+    fn begin(&mut self, phase: Phase, now: DateTime<Utc>) {
+        self.phase = phase;
+        self.started = now;
+    }
+}
+
+impl TaskState {
+    /// show the running session or start one on the first running task
+    pub fn show_pomodoro(&mut self) {
+        if !self.pomodoro.is_active() {
+            let running = self.tasks.tasks.iter().find(|task| task.status == TaskStatus::Progress).map(|task| task.id.clone());
+            match running {
+                Some(id) => self.start_pomodoro(&id),
+                None => return,
+            }
+        }
+        self.set_view(View::Pomodoro);
+    }
+
+    /// attach a new session to a running task, its running session is kept
+    pub fn start_pomodoro(&mut self, id: &TaskId) {
+        if !self.tasks.tasks.iter().any(|task| task.equals(id) && task.status == TaskStatus::Progress) {
+            return;
+        }
+        if self.pomodoro.task.as_ref() == Some(id) {
+            self.set_view(View::Pomodoro);
+            return;
+        }
+
+        self.now = Utc::now();
+        self.pomodoro.task = Some(id.clone());
+        self.pomodoro.begin(Phase::Work, self.now);
+        self.set_view(View::Pomodoro);
+    }
+
+    /// end the session early, the task is stopped
+    pub fn interrupt_pomodoro(&mut self) {
+        if let Some(id) = self.pomodoro.task.take() {
+            self.dispatch(Action::SetStatus(id, TaskStatus::Stop));
+        }
+        self.view = View::List;
+    }
+
+    /// count finished work phases and switch phases, called on every clock tick
+    pub fn tick_pomodoro(&mut self) {
+        let id = match &self.pomodoro.task {
+            Some(id) => id.clone(),
+            None => return,
+        };
+
+        // the task was stopped, finished or removed elsewhere
+        if !self.tasks.tasks.iter().any(|task| task.equals(&id) && task.status == TaskStatus::Progress) {
+            self.pomodoro.task = None;
+            if self.view == View::Pomodoro {
+                self.view = View::List;
+            }
+            return;
+        }
+
+        if self.pomodoro.remaining(self.now) > Duration::zero() {
+            return;
+        }
+        match self.pomodoro.phase {
+            Phase::Work => {
+                self.dispatch(Action::CountPomodoro(id));
+                self.pomodoro.begin(Phase::Break, self.now);
+            },
+            Phase::Break => self.pomodoro.begin(Phase::Work, self.now),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phase_lengths_are_clamped() {
+        let now = Utc::now();
+        let mut pomodoro = Pomodoro::new(i64::MAX, 0);
+        pomodoro.begin(Phase::Work, now);
+        assert_eq!(pomodoro.remaining(now), Duration::minutes(MAX_MINUTES));
+
+        let mut pomodoro = Pomodoro::new(25, i64::MIN);
+        pomodoro.begin(Phase::Break, now);
+        assert_eq!(pomodoro.remaining(now), Duration::minutes(MIN_MINUTES));
+    }
+}
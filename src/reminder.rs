@@ -0,0 +1,220 @@
+use chrono::{Duration as ChronoDuration, Local, NaiveDateTime};
+use druid::widget::prelude::*;
+use druid::widget::Controller;
+use druid::TimerToken;
+use notify_rust::Notification;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+use crate::action::Action;
+use crate::i18n::tr;
+use crate::model::{Task, TaskId, TaskStatus, Tasks};
+use crate::TaskState;
+
+// how often reminders are checked
+const TICK: Duration = Duration::from_secs(5);
+const SNOOZE_MINUTES: i64 = 10;
+// reminders missed for longer are not shown anymore
+const MISSED_HOURS: i64 = 12;
+
+/// what the user picked on a reminder
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReminderAction {
+    Done(TaskId),
+    Snooze(TaskId),
+}
+
+/// shows reminders, desktop notifications in the app
+pub trait Notifier {
+    /// show a reminder, the picked action is sent to reply
+    fn notify(&self, task: &Task, reply: Sender<ReminderAction>);
+}
+
+/// freedesktop notifications on linux, the native ones elsewhere
+pub struct DesktopNotifier;
+
+impl Notifier for DesktopNotifier {
+    fn notify(&self, task: &Task, reply: Sender<ReminderAction>) {
+        let mut notification = Notification::new();
+        notification
+            .appname("Tasking!")
+            .summary(&tr("reminder-title"))
+            .body(task.content.as_str());
+
+        // only the notification server on linux reports actions back
+        #[cfg(all(unix, not(target_os = "macos")))]
+        {
+            notification
+                .action("done", &tr("status-done"))
+                .action("snooze", &tr("reminder-snooze"));
+
+            let id = task.id.clone();
+            thread::spawn(move || match notification.show() {
+                Ok(handle) => handle.wait_for_action(|action| {
+                    let _ = match action {
+                        "done" => reply.send(ReminderAction::Done(id)),
+                        "snooze" => reply.send(ReminderAction::Snooze(id)),
+                        _ => Ok(()),
+                    };
+                }),
+                Err(e) => eprintln!("通知を表示できませんでした: {}", e),
+            });
+        }
+
+        #[cfg(not(all(unix, not(target_os = "macos"))))]
+        {
+            let _ = reply;
+            thread::spawn(move || {
+                if let Err(e) = notification.show() {
+                    eprintln!("通知を表示できませんでした: {}", e);
+                }
+            });
+        }
+    }
+}
+
+/// checks reminder times on a timer and handles the picked actions
+pub struct Reminders {
+    notifier: Box<dyn Notifier>,
+    timer: TimerToken,
+    sender: Sender<ReminderAction>,
+    receiver: Receiver<ReminderAction>,
+    // reminder time already shown per task
+    notified: HashMap<TaskId, NaiveDateTime>,
+}
+
+impl Reminders {
+    /// construct
+    pub fn new(notifier: Box<dyn Notifier>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            notifier: notifier,
+            timer: TimerToken::INVALID,
+            sender: sender,
+            receiver: receiver,
+            notified: HashMap::new(),
+        }
+    }
+
+    fn tick(&mut self, data: &mut TaskState) {
+        let now = Local::now().naive_local();
+
+        while let Ok(action) = self.receiver.try_recv() {
+            match action {
+                ReminderAction::Done(id) => data.dispatch(Action::SetStatus(id, TaskStatus::Done)),
+                ReminderAction::Snooze(id) => {
+                    data.dispatch(Action::SetReminder(id, Some(now + ChronoDuration::minutes(SNOOZE_MINUTES))))
+                },
+            }
+        }
+
+        self.check(&data.tasks, now);
+    }
+
+    /// notify each reminder that came due, once per reminder time
+    fn check(&mut self, tasks: &Tasks, now: NaiveDateTime) {
+        for task in tasks.tasks.iter().filter(|task| task.status != TaskStatus::Done) {
+            let at = match task.reminder_time() {
+                Some(at) => at,
+                None => continue,
+            };
+            if at > now || now - at > ChronoDuration::hours(MISSED_HOURS) || self.notified.get(&task.id) == Some(&at) {
+                continue;
+            }
+
+            self.notified.insert(task.id.clone(), at);
+            self.notifier.notify(task, self.sender.clone());
+        }
+    }
+}
+
+impl<W: Widget<TaskState>> Controller<TaskState, W> for Reminders {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut TaskState, env: &Env) {
+        match event {
+            Event::WindowConnected => {
+                self.timer = ctx.request_timer(TICK);
+            },
+            Event::Timer(token) if *token == self.timer => {
+                self.tick(data);
+                self.timer = ctx.request_timer(TICK);
+                return;
+            },
+            _ => (),
+        }
+
+        child.event(ctx, event, data, env)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::model::SaveTasks;
+
+    /// records the tasks it was asked to show
+    struct RecordingNotifier {
+        shown: Rc<RefCell<Vec<TaskId>>>,
+    }
+
+    impl Notifier for RecordingNotifier {
+        fn notify(&self, task: &Task, _reply: Sender<ReminderAction>) {
+            self.shown.borrow_mut().push(task.id.clone());
+        }
+    }
+
+    fn reminders() -> (Reminders, Rc<RefCell<Vec<TaskId>>>) {
+        let shown = Rc::new(RefCell::new(Vec::new()));
+        let notifier = RecordingNotifier {
+            shown: shown.clone(),
+        };
+        (Reminders::new(Box::new(notifier)), shown)
+    }
+
+    fn task_reminded_at(remind_at: NaiveDateTime) -> Task {
+        let mut task = Task::create(TaskId::generate(), "task".to_string());
+        task.remind_at = Some(remind_at);
+        task
+    }
+
+    fn tasks(tasks: Vec<Task>) -> Tasks {
+        Tasks::from_save_tasks(SaveTasks {
+            tasks: tasks,
+        })
+    }
+
+    #[test]
+    fn notifies_due_reminders_once() {
+        let now = Local::now().naive_local();
+        let due = task_reminded_at(now - ChronoDuration::minutes(1));
+        let ahead = task_reminded_at(now + ChronoDuration::minutes(1));
+        let missed = task_reminded_at(now - ChronoDuration::hours(MISSED_HOURS + 1));
+        let mut done = task_reminded_at(now - ChronoDuration::minutes(1));
+        done.change_status(TaskStatus::Done);
+        let tasks = tasks(vec![due.clone(), ahead, missed, done]);
+
+        let (mut reminders, shown) = reminders();
+        reminders.check(&tasks, now);
+        reminders.check(&tasks, now);
+
+        assert_eq!(*shown.borrow(), vec![due.id]);
+    }
+
+    #[test]
+    fn notifies_again_after_a_new_reminder_time() {
+        let now = Local::now().naive_local();
+        let mut task = task_reminded_at(now - ChronoDuration::minutes(1));
+
+        let (mut reminders, shown) = reminders();
+        reminders.check(&tasks(vec![task.clone()]), now);
+        task.remind_at = Some(now + ChronoDuration::minutes(SNOOZE_MINUTES));
+        reminders.check(&tasks(vec![task.clone()]), now + ChronoDuration::minutes(SNOOZE_MINUTES));
+
+        assert_eq!(*shown.borrow(), vec![task.id.clone(), task.id]);
+    }
+}
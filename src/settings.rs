@@ -0,0 +1,266 @@
+use druid::im::Vector;
+use druid::widget::prelude::*;
+use druid::widget::{Checkbox, Controller, CrossAxisAlignment, Flex, Label, RadioGroup, Scroll, Stepper, TextBox};
+use druid::{Data, Key, Lens, Selector, WidgetExt};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+use crate::i18n::{self, tr, Locale};
+use crate::model::{Task, TaskStatus};
+use crate::theme::{self, Theme, ThemeName};
+use crate::{make_button, TaskState, BLOCK_HEIGHT, BLOCK_SPACE};
+
+// font sizes read by the widgets
+pub const TEXT_SIZE: Key<f64> = Key::new("tasking.settings.text-size");
+pub const TASK_TEXT_SIZE: Key<f64> = Key::new("tasking.settings.task-text-size");
+pub const SMALL_TEXT_SIZE: Key<f64> = Key::new("tasking.settings.small-text-size");
+pub const LARGE_TEXT_SIZE: Key<f64> = Key::new("tasking.settings.large-text-size");
+
+// store the settings and apply the data file and autosave delay
+pub const SAVE_SETTINGS: Selector = Selector::new("tasking.settings.save");
+
+/// ui language
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Data)]
+pub enum Language {
+    System,
+    Japanese,
+    English,
+}
+
+impl Language {
+    pub fn languages() -> Vec<Self> {
+        vec![Self::System, Self::Japanese, Self::English]
+    }
+
+    /// locale of the ui strings
+    pub fn locale(&self) -> Locale {
+        match self {
+            Self::System => Locale::system(),
+            Self::Japanese => Locale::Japanese,
+            Self::English => Locale::English,
+        }
+    }
+}
+
+// each language is named in itself
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::System => tr("language-system"),
+            Self::Japanese => "日本語".to_string(),
+            Self::English => "English".to_string(),
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// order of the task list, the stored order is kept
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Data)]
+pub enum SortOrder {
+    Manual,
+    Status,
+    Created,
+    Due,
+}
+
+impl SortOrder {
+    pub fn orders() -> Vec<Self> {
+        vec![Self::Manual, Self::Status, Self::Created, Self::Due]
+    }
+
+    /// tasks in this order
+    pub fn apply(&self, mut tasks: Vector<Task>) -> Vector<Task> {
+        match self {
+            Self::Manual => (),
+            Self::Status => {
+                let statuses = TaskStatus::statuses();
+                tasks.sort_by(|a, b| {
+                    let index = |task: &Task| statuses.iter().position(|status| *status == task.status);
+                    index(a).cmp(&index(b))
+                });
+            },
+            Self::Created => tasks.sort_by(|a, b| a.created.cmp(&b.created)),
+            // tasks without a due date come last
+            Self::Due => tasks.sort_by(|a, b| (a.due.is_none(), a.due).cmp(&(b.due.is_none(), b.due))),
+        }
+        tasks
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::Manual => tr("sort-manual"),
+            Self::Status => tr("sort-status"),
+            Self::Created => tr("sort-created"),
+            Self::Due => tr("sort-due"),
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// user settings, stored in config.json
+#[derive(Serialize, Deserialize, Debug, Clone, Data, Lens)]
+#[serde(default)]
+pub struct Settings {
+    // empty uses the storage default
+    pub data_file: String,
+    pub theme: ThemeName,
+    pub text_size: f64,
+    pub task_text_size: f64,
+    pub language: Language,
+    pub confirm_delete: bool,
+    pub sort: SortOrder,
+    pub autosave_seconds: f64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            data_file: "".to_string(),
+            theme: ThemeName::Light,
+            text_size: 18.0,
+            task_text_size: 14.0,
+            language: Language::System,
+            confirm_delete: false,
+            sort: SortOrder::Manual,
+            autosave_seconds: 0.8,
+        }
+    }
+}
+
+impl Settings {
+    /// wait for changes to settle before saving
+    pub fn autosave_delay(&self) -> Duration {
+        Duration::from_secs_f64(self.autosave_seconds.max(0.1))
+    }
+
+    /// set the font size keys
+    pub fn configure(&self, env: &mut Env) {
+        env.set(TEXT_SIZE, self.text_size);
+        env.set(TASK_TEXT_SIZE, self.task_text_size);
+        env.set(SMALL_TEXT_SIZE, self.task_text_size - 2.0);
+        env.set(LARGE_TEXT_SIZE, self.text_size * 2.0);
+    }
+}
+
+/// reloads the theme and the ui strings as soon as another one is picked
+struct LiveSettings {
+    language: Option<Language>,
+}
+
+impl<W: Widget<TaskState>> Controller<TaskState, W> for LiveSettings {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut TaskState, env: &Env) {
+        child.event(ctx, event, data, env);
+
+        if data.settings.theme != data.theme.name {
+            data.theme = Theme::load(data.settings.theme);
+        }
+        if self.language != Some(data.settings.language) {
+            self.language = Some(data.settings.language);
+            i18n::set_locale(data.settings.language.locale());
+        }
+    }
+}
+
+/// muted label above a field
+pub fn make_heading<T: Data>(key: &str) -> impl Widget<T> {
+    Label::new(tr(key))
+        .with_text_size(TASK_TEXT_SIZE)
+        .with_text_color(theme::MUTED)
+}
+
+fn make_size_row(lens: impl Lens<Settings, f64> + Clone + 'static) -> impl Widget<Settings> {
+    Flex::row()
+        .with_child(
+            Label::new(|size: &f64, _: &Env| format!("{}", size))
+                .with_text_size(TASK_TEXT_SIZE)
+                .lens(lens.clone()),
+        )
+        .with_child(
+            Stepper::new()
+                .with_range(10.0, 32.0)
+                .with_step(1.0)
+                .lens(lens),
+        )
+}
+
+/// edits the settings, font sizes and theme change at once
+pub fn make_settings_view() -> impl Widget<TaskState> {
+    let form = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(make_heading("settings-data-file"))
+        .with_child(
+            TextBox::new()
+                .with_placeholder("task.json")
+                .with_text_size(TASK_TEXT_SIZE)
+                .expand_width()
+                .lens(Settings::data_file),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(make_heading("settings-theme"))
+        .with_child(
+            RadioGroup::column(ThemeName::names().into_iter().map(|name| (name.to_string(), name)).collect::<Vec<_>>())
+                .lens(Settings::theme),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(make_heading("settings-text-size"))
+        .with_child(make_size_row(Settings::text_size))
+        .with_child(make_heading("settings-task-text-size"))
+        .with_child(make_size_row(Settings::task_text_size))
+        .with_spacer(BLOCK_SPACE)
+        .with_child(make_heading("settings-language"))
+        .with_child(
+            RadioGroup::column(Language::languages().into_iter().map(|language| (language.to_string(), language)).collect::<Vec<_>>())
+                .lens(Settings::language),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(make_heading("settings-sort"))
+        .with_child(
+            RadioGroup::column(SortOrder::orders().into_iter().map(|order| (order.to_string(), order)).collect::<Vec<_>>())
+                .lens(Settings::sort),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(
+            Checkbox::new(tr("settings-confirm-delete"))
+                .lens(Settings::confirm_delete),
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(make_heading("settings-autosave"))
+        .with_child(
+            Flex::row()
+                .with_child(
+                    Label::new(|seconds: &f64, _: &Env| format!("{:.1}", seconds))
+                        .with_text_size(TASK_TEXT_SIZE),
+                )
+                .with_child(
+                    Stepper::new()
+                        .with_range(0.1, 10.0)
+                        .with_step(0.1),
+                )
+                .lens(Settings::autosave_seconds),
+        )
+        .lens(TaskState::settings);
+
+    Flex::column()
+        .with_flex_child(
+            Scroll::new(form.padding((0.0, 0.0, BLOCK_SPACE, 0.0)))
+                .vertical()
+                .expand(),
+            1.0,
+        )
+        .with_spacer(BLOCK_SPACE)
+        .with_child(
+            make_button::<TaskState>(tr("back"), TEXT_SIZE, theme::DARK)
+                .on_click(|ctx, _, _| {
+                    ctx.submit_command(SAVE_SETTINGS);
+                })
+                .expand_width()
+                .fix_height(BLOCK_HEIGHT),
+        )
+        .padding(BLOCK_SPACE)
+        .controller(LiveSettings {
+            language: None,
+        })
+}
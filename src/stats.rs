@@ -0,0 +1,114 @@
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use druid::piet::{Text, TextLayout, TextLayoutBuilder};
+use druid::widget::prelude::*;
+use druid::widget::Painter;
+use druid::{FontFamily, Point, Rect};
+
+use crate::i18n::tr_args;
+use crate::model::{TaskStatus, Tasks};
+use crate::{theme, TaskState};
+
+const DAYS: i64 = 7;
+const WEEKS: i64 = 4;
+
+/// counts and completion figures of the task list
+pub struct Stats {
+    pub counts: Vec<(TaskStatus, usize)>,
+    // completed per day, oldest first
+    pub days: Vec<(NaiveDate, usize)>,
+    // completed per week starting on monday, oldest first
+    pub weeks: Vec<(NaiveDate, usize)>,
+    // from 新規 to 完了
+    pub average: Option<Duration>,
+}
+
+impl Stats {
+    /// figures up to today
+    pub fn from_tasks(tasks: &Tasks, today: NaiveDate) -> Self {
+        let counts = TaskStatus::statuses()
+            .into_iter()
+            .map(|status| {
+                let count = tasks.tasks.iter().filter(|task| task.status == status).count();
+                (status, count)
+            })
+            .collect();
+
+        let completed: Vec<NaiveDate> = tasks.tasks.iter()
+            .filter_map(|task| task.completed_at())
+            .map(|at| at.with_timezone(&Local).date_naive())
+            .collect();
+
+        let days = (0..DAYS).rev()
+            .map(|offset| today - Duration::days(offset))
+            .map(|day| (day, completed.iter().filter(|date| **date == day).count()))
+            .collect();
+
+        let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+        let weeks = (0..WEEKS).rev()
+            .map(|offset| monday - Duration::weeks(offset))
+            .map(|start| {
+                let count = completed.iter().filter(|date| **date >= start && **date < start + Duration::weeks(1)).count();
+                (start, count)
+            })
+            .collect();
+
+        let lead_times: Vec<Duration> = tasks.tasks.iter()
+            .filter_map(|task| Some(task.completed_at()? - task.created?))
+            .collect();
+        let average = match lead_times.len() {
+            0 => None,
+            count => Some(lead_times.iter().fold(Duration::zero(), |total, time| total + *time) / count as i32),
+        };
+
+        Self {
+            counts: counts,
+            days: days,
+            weeks: weeks,
+            average: average,
+        }
+    }
+
+    /// figures for the current tasks
+    pub fn today(tasks: &Tasks) -> Self {
+        Self::from_tasks(tasks, Local::now().date_naive())
+    }
+}
+
+/// lead time in days, hours or minutes
+pub fn format_lead_time(duration: Duration) -> String {
+    match (duration.num_days(), duration.num_hours() % 24) {
+        (0, 0) => tr_args("duration-minutes", &[("minutes", &duration.num_minutes())]),
+        (0, hours) => tr_args("duration-hours", &[("hours", &hours)]),
+        (days, hours) => tr_args("duration-days", &[("days", &days), ("hours", &hours)]),
+    }
+}
+
+/// bars of tasks completed per day
+pub fn make_chart() -> Painter<TaskState> {
+    Painter::new(|ctx, data: &TaskState, env| {
+        let stats = Stats::today(&data.tasks);
+        let size = ctx.size();
+        let label_height = 18.0;
+        let slot = size.width / stats.days.len() as f64;
+        let max = stats.days.iter().map(|(_, count)| *count).max().unwrap_or(0).max(1) as f64;
+        let chart_height = size.height - label_height * 2.0;
+
+        for (index, (day, count)) in stats.days.iter().enumerate() {
+            let left = slot * index as f64;
+            let height = chart_height * *count as f64 / max;
+            let bar = Rect::new(left + slot * 0.2, label_height + chart_height - height, left + slot * 0.8, label_height + chart_height);
+            ctx.fill(bar, &env.get(theme::SUCCESS));
+
+            for (text, top) in [(count.to_string(), bar.y0 - label_height), (day.format("%m/%d").to_string(), size.height - label_height)] {
+                let layout = ctx.text()
+                    .new_text_layout(text)
+                    .font(FontFamily::SYSTEM_UI, 11.0)
+                    .text_color(env.get(theme::MUTED))
+                    .build()
+                    .expect("テキストを配置できませんでした");
+                let x = left + (slot - layout.size().width) / 2.0;
+                ctx.draw_text(&layout, Point::new(x, top));
+            }
+        }
+    })
+}
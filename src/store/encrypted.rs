@@ -0,0 +1,257 @@
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::TaskStore;
+use crate::model::SaveTasks;
+
+const FORMAT: &str = "tasking-encrypted-v1";
+
+/// encrypted file contents
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Envelope {
+    format: String,
+    salt: String,
+    nonce: String,
+    data: String,
+}
+
+/// key derived from the passphrase
+struct Secret {
+    key: [u8; 32],
+    salt: [u8; 16],
+}
+
+impl Secret {
+    fn derive(passphrase: &str, salt: [u8; 16]) -> Self {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .expect("鍵を生成できませんでした");
+
+        Self {
+            key: key,
+            salt: salt,
+        }
+    }
+
+    /// new key with a random salt
+    fn generate(passphrase: &str) -> Self {
+        let mut salt = [0u8; 16];
+        getrandom::getrandom(&mut salt).expect("乱数を生成できませんでした");
+        Self::derive(passphrase, salt)
+    }
+
+    fn seal(&self, tasks: &SaveTasks) -> String {
+        let mut nonce = [0u8; 24];
+        getrandom::getrandom(&mut nonce).expect("乱数を生成できませんでした");
+
+        let plaintext = serde_json::to_string(tasks).expect("シリアライズできませんでした");
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(&self.key));
+        let data = cipher
+            .encrypt(XNonce::from_slice(&nonce), plaintext.as_bytes())
+            .expect("暗号化できませんでした");
+
+        let envelope = Envelope {
+            format: FORMAT.to_string(),
+            salt: hex::encode(self.salt),
+            nonce: hex::encode(nonce),
+            data: hex::encode(data),
+        };
+        serde_json::to_string(&envelope).expect("シリアライズできませんでした")
+    }
+
+    fn open(&self, envelope: &Envelope) -> Option<SaveTasks> {
+        let nonce = hex::decode(&envelope.nonce).ok()?;
+        let data = hex::decode(&envelope.data).ok()?;
+        if nonce.len() != 24 {
+            return None
+        }
+
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(&self.key));
+        let plaintext = cipher.decrypt(XNonce::from_slice(&nonce), data.as_ref()).ok()?;
+        serde_json::from_slice::<SaveTasks>(&plaintext).ok()
+    }
+}
+
+/// passphrase encrypted json file, locked until unlocked
+pub struct EncryptedStore {
+    path: PathBuf,
+    secret: Option<Secret>,
+}
+
+impl EncryptedStore {
+    /// construct locked
+    pub fn new(filename: &str) -> Self {
+        Self {
+            path: PathBuf::from(filename),
+            secret: None,
+        }
+    }
+
+    /// the file holds encrypted tasks
+    pub fn is_encrypted(filename: &str) -> bool {
+        read_envelope(Path::new(filename)).is_some()
+    }
+
+    /// encrypt tasks into the file with a new passphrase
+    pub fn create(filename: &str, passphrase: &str, tasks: &SaveTasks) -> Self {
+        let mut store = Self {
+            path: PathBuf::from(filename),
+            secret: Some(Secret::generate(passphrase)),
+        };
+        store.save(tasks);
+
+        store
+    }
+
+    /// decrypt the file with passphrase
+    pub fn decrypt(filename: &str, passphrase: &str) -> Option<SaveTasks> {
+        let mut store = Self::new(filename);
+        match store.unlock(passphrase) {
+            true => store.load(),
+            false => None,
+        }
+    }
+}
+
+impl TaskStore for EncryptedStore {
+    fn load(&mut self) -> Option<SaveTasks> {
+        let envelope = read_envelope(&self.path)?;
+        self.secret.as_ref()?.open(&envelope)
+    }
+
+    fn save(&mut self, tasks: &SaveTasks) {
+        let serialized = self.encode(tasks);
+        let mut file = fs::File::create(&self.path).expect(format!("{} ファイルが開けませんでした", self.path.display()).as_str());
+        writeln!(file, "{}", serialized).expect("ファイルに書き込めませんでした");
+    }
+
+    fn is_locked(&self) -> bool {
+        self.secret.is_none()
+    }
+
+    fn unlock(&mut self, passphrase: &str) -> bool {
+        let envelope = match read_envelope(&self.path) {
+            Some(envelope) => envelope,
+            None => return false,
+        };
+        let salt = match hex::decode(&envelope.salt).ok().and_then(|salt| <[u8; 16]>::try_from(salt).ok()) {
+            Some(salt) => salt,
+            None => return false,
+        };
+
+        let secret = Secret::derive(passphrase, salt);
+        if secret.open(&envelope).is_none() {
+            return false
+        }
+        self.secret = Some(secret);
+
+        true
+    }
+
+    fn encode(&self, tasks: &SaveTasks) -> String {
+        self.secret.as_ref().expect("暗号化ファイルがロックされています").seal(tasks)
+    }
+
+    fn decode(&self, serialized: &str) -> Option<SaveTasks> {
+        let envelope = serde_json::from_str::<Envelope>(serialized).ok()?;
+        self.secret.as_ref()?.open(&envelope)
+    }
+}
+
+fn read_envelope(path: &Path) -> Option<Envelope> {
+    let serialized = fs::read_to_string(path).ok()?;
+    serde_json::from_str::<Envelope>(&serialized).ok().filter(|envelope| envelope.format == FORMAT)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::model::{Task, TaskId};
+
+    fn encrypted_path(name: &str) -> String {
+        let path = env::temp_dir().join(format!("tasking-{}-{}.json", name, TaskId::generate()));
+        path.to_string_lossy().to_string()
+    }
+
+    fn tasks() -> SaveTasks {
+        SaveTasks {
+            tasks: vec![Task::create(TaskId::generate(), "secret".to_string())],
+        }
+    }
+
+    #[test]
+    fn unlock_opens_created_file() {
+        let path = encrypted_path("encrypted");
+        let tasks = tasks();
+        EncryptedStore::create(&path, "passphrase", &tasks);
+
+        assert!(EncryptedStore::is_encrypted(&path));
+        assert!(!fs::read_to_string(&path).expect("file").contains("secret"));
+
+        let mut store = EncryptedStore::new(&path);
+        assert!(store.is_locked());
+        assert!(store.load().is_none());
+        assert!(store.unlock("passphrase"));
+        assert!(!store.is_locked());
+        assert_eq!(store.load().expect("tasks").tasks, tasks.tasks);
+
+        fs::remove_file(&path).expect("remove");
+    }
+
+    #[test]
+    fn wrong_passphrase_stays_locked() {
+        let path = encrypted_path("encrypted-wrong");
+        EncryptedStore::create(&path, "passphrase", &tasks());
+
+        let mut store = EncryptedStore::new(&path);
+        assert!(!store.unlock("wrong"));
+        assert!(store.is_locked());
+        assert!(store.load().is_none());
+
+        fs::remove_file(&path).expect("remove");
+    }
+
+    #[test]
+    fn new_passphrase_replaces_old_one() {
+        let path = encrypted_path("encrypted-passwd");
+        let tasks = tasks();
+        EncryptedStore::create(&path, "old", &tasks);
+
+        // what passwd does
+        let decrypted = EncryptedStore::decrypt(&path, "old").expect("tasks");
+        EncryptedStore::create(&path, "new", &decrypted);
+
+        assert!(EncryptedStore::decrypt(&path, "old").is_none());
+        assert_eq!(EncryptedStore::decrypt(&path, "new").expect("tasks").tasks, tasks.tasks);
+
+        fs::remove_file(&path).expect("remove");
+    }
+
+    #[test]
+    fn decode_reads_encoded_backup() {
+        let path = encrypted_path("encrypted-backup");
+        let tasks = tasks();
+        let store = EncryptedStore::create(&path, "passphrase", &SaveTasks::new());
+
+        let backup = store.encode(&tasks);
+        assert!(!backup.contains("secret"));
+        assert_eq!(store.decode(&backup).expect("tasks").tasks, tasks.tasks);
+
+        // a backup needs the key of its file
+        let other_path = encrypted_path("encrypted-other");
+        let other = EncryptedStore::create(&other_path, "other", &SaveTasks::new());
+        assert!(other.decode(&backup).is_none());
+        assert!(EncryptedStore::new(&path).decode(&backup).is_none());
+
+        fs::remove_file(&path).expect("remove");
+        fs::remove_file(&other_path).expect("remove");
+    }
+}
@@ -0,0 +1,276 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{Change, TaskStore};
+use crate::model::SaveTasks;
+
+// journal entries before the snapshot is rewritten
+const COMPACT_AFTER: usize = 200;
+
+/// one recorded mutation
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Entry {
+    pub seq: u64,
+    // unix time in seconds
+    pub at: u64,
+    pub change: Change,
+    // full list for Change::Replace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tasks: Option<SaveTasks>,
+}
+
+/// snapshot file, includes every entry up to seq
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+struct Snapshot {
+    seq: u64,
+    tasks: SaveTasks,
+}
+
+/// append-only json-lines journal with snapshot compaction
+pub struct JournalStore {
+    path: PathBuf,
+    snapshot_path: PathBuf,
+    archive_path: PathBuf,
+    seq: u64,
+    uncompacted: usize,
+}
+
+impl JournalStore {
+    /// construct
+    pub fn new(filename: &str) -> Self {
+        let mut store = Self {
+            path: PathBuf::from(filename),
+            snapshot_path: PathBuf::from(format!("{}.snapshot.json", filename)),
+            archive_path: PathBuf::from(format!("{}.archive", filename)),
+            seq: 0,
+            uncompacted: 0,
+        };
+        store.load();
+
+        store
+    }
+
+    /// every recorded entry, oldest first
+    pub fn history(&self) -> Vec<Entry> {
+        let mut entries = read_entries(&self.archive_path);
+        entries.append(&mut read_entries(&self.path));
+
+        entries
+    }
+
+    fn read_snapshot(&self) -> Option<Snapshot> {
+        let serialized = fs::read_to_string(&self.snapshot_path).ok()?;
+        serde_json::from_str::<Snapshot>(&serialized).ok()
+    }
+
+    fn append(&mut self, change: Change, tasks: Option<SaveTasks>) {
+        self.seq += 1;
+        let entry = Entry {
+            seq: self.seq,
+            at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            change: change,
+            tasks: tasks,
+        };
+
+        let serialized = serde_json::to_string(&entry).expect("シリアライズできませんでした");
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .expect(format!("{} ファイルが開けませんでした", self.path.display()).as_str());
+        writeln!(file, "{}", serialized).expect("ファイルに書き込めませんでした");
+        file.sync_data().expect("ファイルに書き込めませんでした");
+
+        self.uncompacted += 1;
+    }
+
+    /// write a snapshot and move the journal into the archive
+    fn compact(&mut self, tasks: &SaveTasks) {
+        let snapshot = Snapshot {
+            seq: self.seq,
+            tasks: tasks.clone(),
+        };
+        let serialized = serde_json::to_string(&snapshot).expect("シリアライズできませんでした");
+
+        // replace atomically so a crash keeps the old snapshot
+        let temporary = self.snapshot_path.with_extension("tmp");
+        fs::write(&temporary, serialized).expect("ファイルに書き込めませんでした");
+        fs::rename(&temporary, &self.snapshot_path).expect("ファイルに書き込めませんでした");
+
+        if let Ok(journal) = fs::read_to_string(&self.path) {
+            let mut archive = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.archive_path)
+                .expect(format!("{} ファイルが開けませんでした", self.archive_path.display()).as_str());
+            archive.write_all(journal.as_bytes()).expect("ファイルに書き込めませんでした");
+            fs::File::create(&self.path).expect("ファイルに書き込めませんでした");
+        }
+
+        self.uncompacted = 0;
+    }
+
+    fn compact_if_needed(&mut self, tasks: &SaveTasks) {
+        if self.uncompacted >= COMPACT_AFTER {
+            self.compact(tasks);
+        }
+    }
+}
+
+impl TaskStore for JournalStore {
+    fn load(&mut self) -> Option<SaveTasks> {
+        let snapshot = self.read_snapshot();
+        let journal = read_entries(&self.path);
+        if snapshot.is_none() && journal.is_empty() && !self.path.exists() {
+            return None
+        }
+
+        let Snapshot { seq, mut tasks } = snapshot.unwrap_or_default();
+        self.seq = seq;
+        self.uncompacted = 0;
+
+        // entries up to seq are already in the snapshot
+        for entry in journal.into_iter().filter(|entry| entry.seq > seq) {
+            replay(&mut tasks, &entry);
+            self.seq = entry.seq;
+            self.uncompacted += 1;
+        }
+
+        Some(tasks)
+    }
+
+    fn save(&mut self, tasks: &SaveTasks) {
+        self.append(Change::Replace, Some(tasks.clone()));
+        self.compact_if_needed(tasks);
+    }
+
+    fn apply(&mut self, changes: &[Change], tasks: &SaveTasks) {
+        // the snapshot already holds the other changes of the batch
+        if changes.iter().any(|change| matches!(change, Change::Replace)) {
+            self.save(tasks);
+            return;
+        }
+
+        for change in changes.iter() {
+            self.append(change.clone(), None);
+        }
+        self.compact_if_needed(tasks);
+    }
+}
+
+/// apply a recorded entry to tasks
+pub fn replay(tasks: &mut SaveTasks, entry: &Entry) {
+    match &entry.change {
+        Change::Add(task) => tasks.tasks.push(task.clone()),
+        Change::Status(id, status) => {
+            if let Some(task) = tasks.tasks.iter_mut().find(|task| task.equals(id)) {
+                // history and tracked time follow in an update
+                task.status = status.clone();
+            }
+        },
+        Change::Edit(id, content) => {
+            if let Some(task) = tasks.tasks.iter_mut().find(|task| task.equals(id)) {
+                task.edit(content.clone());
+            }
+        },
+        Change::Update(updated) => {
+            if let Some(task) = tasks.tasks.iter_mut().find(|task| task.equals(&updated.id)) {
+                *task = updated.clone();
+            }
+        },
+        Change::Remove(id) => tasks.tasks.retain(|task| !task.equals(id)),
+        Change::Clear => tasks.tasks.clear(),
+        Change::Sort(ids) => {
+            // unknown tasks keep their place at the end
+            tasks.tasks.sort_by_key(|task| ids.iter().position(|id| task.equals(id)).unwrap_or(ids.len()));
+        },
+        Change::Replace => {
+            if let Some(replaced) = &entry.tasks {
+                *tasks = replaced.clone();
+            }
+        },
+    }
+}
+
+/// read entries, skipping a torn last line
+fn read_entries(path: &Path) -> Vec<Entry> {
+    match fs::File::open(path) {
+        Ok(file) => BufReader::new(file)
+            .lines()
+            .filter_map(|line| line.ok())
+            .filter_map(|line| serde_json::from_str::<Entry>(&line).ok())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::action::{reduce, Action};
+    use crate::model::{Task, TaskId, TaskStatus, Tasks};
+
+    fn journal_path(name: &str) -> String {
+        let path = env::temp_dir().join(format!("tasking-{}-{}.jsonl", name, TaskId::generate()));
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn replay_replace_batch_once() {
+        let path = journal_path("replace");
+        let imported = Task::create(TaskId::generate(), "imported".to_string());
+        let added = Task::create(TaskId::generate(), "added".to_string());
+        let tasks = SaveTasks {
+            tasks: vec![imported.clone(), added.clone()],
+        };
+
+        let mut store = JournalStore::new(&path);
+        store.apply(&[Change::Replace, Change::Add(added)], &tasks);
+
+        let loaded = JournalStore::new(&path).load().expect("journal");
+        assert_eq!(loaded.tasks, tasks.tasks);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn replay_changes() {
+        let path = journal_path("changes");
+        let task = Task::create(TaskId::generate(), "task".to_string());
+        let mut edited = task.clone();
+        edited.edit("edited".to_string());
+
+        let mut store = JournalStore::new(&path);
+        store.apply(&[Change::Add(task.clone()), Change::Edit(task.id.clone(), "edited".to_string())], &SaveTasks {
+            tasks: vec![edited.clone()],
+        });
+
+        let loaded = JournalStore::new(&path).load().expect("journal");
+        assert_eq!(loaded.tasks, vec![edited]);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn replay_keeps_the_status_history() {
+        let path = journal_path("status");
+        let mut store = JournalStore::new(&path);
+        let mut tasks = Tasks::new();
+        store.apply(&reduce(&mut tasks, Action::Add("task".to_string())), &tasks.to_save_tasks());
+        let id = tasks.tasks[0].id.clone();
+        for status in [TaskStatus::Stop, TaskStatus::Done] {
+            store.apply(&reduce(&mut tasks, Action::SetStatus(id.clone(), status)), &tasks.to_save_tasks());
+        }
+
+        let loaded = JournalStore::new(&path).load().expect("journal");
+        assert_eq!(loaded.tasks[0].status_history.len(), 2);
+        assert!(loaded.tasks[0].completed_at().is_some());
+
+        let _ = fs::remove_file(&path);
+    }
+}
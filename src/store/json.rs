@@ -0,0 +1,33 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use super::TaskStore;
+use crate::model::SaveTasks;
+
+/// whole list in a single json file
+pub struct JsonStore {
+    path: PathBuf,
+}
+
+impl JsonStore {
+    /// construct
+    pub fn new(filename: &str) -> Self {
+        Self {
+            path: PathBuf::from(filename),
+        }
+    }
+}
+
+impl TaskStore for JsonStore {
+    fn load(&mut self) -> Option<SaveTasks> {
+        let serialized = fs::read_to_string(&self.path).ok()?;
+        serde_json::from_str::<SaveTasks>(&serialized).ok()
+    }
+
+    fn save(&mut self, tasks: &SaveTasks) {
+        let serialized = serde_json::to_string(tasks).expect("シリアライズできませんでした");
+        let mut file = fs::File::create(&self.path).expect(format!("{} ファイルが開けませんでした", self.path.display()).as_str());
+        writeln!(file, "{}", serialized).expect("ファイルに書き込めませんでした");
+    }
+}
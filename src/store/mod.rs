@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::config::Storage;
+use crate::i18n::{tr, tr_args};
+use crate::model::{SaveTasks, Task, TaskId, TaskStatus};
+
+mod encrypted;
+mod journal;
+mod json;
+mod sqlite;
+
+pub use encrypted::EncryptedStore;
+pub use journal::JournalStore;
+pub use json::JsonStore;
+pub use sqlite::SqliteStore;
+
+/// a single mutation of the task list
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Change {
+    Add(Task),
+    Status(TaskId, TaskStatus),
+    Edit(TaskId, String),
+    // every field of the task, for fields without a change of their own
+    Update(Task),
+    Remove(TaskId),
+    Clear,
+    Sort(Vec<TaskId>),
+    // whole list replaced, e.g. undo or import
+    Replace,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Self::Add(task) => tr_args("change-add", &[("id", &task.id), ("content", &task.content)]),
+            Self::Status(id, status) => tr_args("change-status", &[("id", id), ("status", status)]),
+            Self::Edit(id, content) => tr_args("change-edit", &[("id", id), ("content", content)]),
+            Self::Update(task) => tr_args("change-update", &[("id", &task.id), ("content", &task.content)]),
+            Self::Remove(id) => tr_args("change-remove", &[("id", id)]),
+            Self::Clear => tr("change-clear"),
+            Self::Sort(_) => tr("change-sort"),
+            Self::Replace => tr("change-replace"),
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// task persistence backend
+pub trait TaskStore: Send {
+    /// load all tasks, None if nothing could be read
+    fn load(&mut self) -> Option<SaveTasks>;
+
+    /// replace all tasks
+    fn save(&mut self, tasks: &SaveTasks);
+
+    /// write changes, tasks is the state after all of them
+    fn apply(&mut self, _changes: &[Change], tasks: &SaveTasks) {
+        self.save(tasks);
+    }
+
+    /// waiting for a passphrase
+    fn is_locked(&self) -> bool {
+        false
+    }
+
+    /// unlock with passphrase, true on success
+    fn unlock(&mut self, _passphrase: &str) -> bool {
+        true
+    }
+
+    /// serialize tasks for a backup copy
+    fn encode(&self, tasks: &SaveTasks) -> String {
+        serde_json::to_string(tasks).expect("シリアライズできませんでした")
+    }
+
+    /// deserialize a backup copy
+    fn decode(&self, serialized: &str) -> Option<SaveTasks> {
+        serde_json::from_str::<SaveTasks>(serialized).ok()
+    }
+}
+
+/// open a store for the data file with a backend
+pub fn open(filename: &str, storage: Storage) -> Result<Box<dyn TaskStore>, String> {
+    let store: Box<dyn TaskStore> = match storage {
+        Storage::Sqlite => Box::new(SqliteStore::open(filename)?),
+        Storage::Journal => Box::new(JournalStore::new(filename)),
+        Storage::Json if EncryptedStore::is_encrypted(filename) => Box::new(EncryptedStore::new(filename)),
+        Storage::Json => Box::new(JsonStore::new(filename)),
+    };
+    Ok(store)
+}
+
+/// open a store with the backend its extension stands for, json otherwise
+pub fn open_file(filename: &str) -> Result<Box<dyn TaskStore>, String> {
+    open(filename, Storage::from_filename(filename).unwrap_or(Storage::Json))
+}
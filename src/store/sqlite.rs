@@ -0,0 +1,280 @@
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+use super::{Change, JsonStore, TaskStore};
+use crate::i18n::tr_args;
+use crate::model::{SaveTasks, Task, TaskId, TaskStatus};
+
+const SCHEMA_VERSION: i64 = 4;
+
+// data holds the whole task as json, the other columns are for queries
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS tasks (
+        id TEXT PRIMARY KEY,
+        content TEXT NOT NULL,
+        status TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        data TEXT
+    );
+    CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+";
+
+// set once task.json was offered to the database, clearing it later must not import again
+const JSON_IMPORTED: &str = "json_imported";
+
+// version 1 used integer ids from a counter
+const MIGRATE_V1: &str = "
+    ALTER TABLE tasks RENAME TO tasks_v1;
+    CREATE TABLE tasks (
+        id TEXT PRIMARY KEY,
+        content TEXT NOT NULL,
+        status TEXT NOT NULL,
+        position INTEGER NOT NULL
+    );
+    INSERT INTO tasks (id, content, status, position)
+        SELECT CAST(id AS TEXT), content, status, position FROM tasks_v1;
+    DROP TABLE tasks_v1;
+    DROP TABLE IF EXISTS meta;
+";
+
+// version 2 kept only content and status
+const MIGRATE_V2: &str = "
+    ALTER TABLE tasks ADD COLUMN data TEXT;
+";
+
+// version 3 had no meta table, it already had its import on the first start
+const MIGRATE_V3: &str = "
+    CREATE TABLE meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    INSERT INTO meta (key, value) VALUES ('json_imported', '1');
+";
+
+/// tasks in an embedded sqlite database, written incrementally
+pub struct SqliteStore {
+    connection: Connection,
+}
+
+impl SqliteStore {
+    /// open or create the database, fails on files that are not sqlite
+    pub fn open(filename: &str) -> Result<Self, String> {
+        let failed = |e: rusqlite::Error| tr_args("store-open-failed", &[("file", &filename), ("error", &e)]);
+        let connection = Connection::open(Path::new(filename)).map_err(failed)?;
+        migrate(&connection).map_err(failed)?;
+        connection.execute_batch(SCHEMA).map_err(failed)?;
+        connection.pragma_update(None, "user_version", SCHEMA_VERSION).map_err(failed)?;
+
+        Ok(Self {
+            connection: connection,
+        })
+    }
+
+    /// database has never been written
+    pub fn is_empty(&self) -> bool {
+        let count: u32 = self.connection
+            .query_row("SELECT COUNT(*) FROM tasks", [], |row| row.get(0))
+            .expect("データベースを読み込めませんでした");
+        count == 0
+    }
+
+    /// copy tasks from a json file into an empty database
+    pub fn migrate_from_json(&mut self, filename: &str) -> bool {
+        if !self.is_empty() {
+            return false
+        }
+
+        match JsonStore::new(filename).load() {
+            Some(tasks) => {
+                self.save(&tasks);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// import task.json on the first start only, even if nothing could be read
+    pub fn migrate_from_json_once(&mut self, filename: &str) -> bool {
+        if self.is_marked(JSON_IMPORTED) {
+            return false
+        }
+        self.mark(JSON_IMPORTED);
+        self.migrate_from_json(filename)
+    }
+
+    fn is_marked(&self, key: &str) -> bool {
+        self.connection
+            .query_row("SELECT COUNT(*) > 0 FROM meta WHERE key = ?1", params![key], |row| row.get(0))
+            .expect("データベースを読み込めませんでした")
+    }
+
+    fn mark(&self, key: &str) {
+        self.connection
+            .execute("INSERT OR REPLACE INTO meta (key, value) VALUES (?1, '1')", params![key])
+            .expect("データベースに書き込めませんでした");
+    }
+}
+
+impl TaskStore for SqliteStore {
+    fn load(&mut self) -> Option<SaveTasks> {
+        let mut statement = self.connection
+            .prepare("SELECT id, content, status, data FROM tasks ORDER BY position, id")
+            .ok()?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, Option<String>>(3)?))
+            })
+            .ok()?;
+
+        let mut tasks = SaveTasks::new();
+        for row in rows {
+            let (id, content, status, data) = row.ok()?;
+            let task = match data.and_then(|data| serde_json::from_str::<Task>(&data).ok()) {
+                Some(task) => task,
+                None => {
+                    let mut task = Task::create(TaskId::from(id.as_str()), content);
+                    task.status = status_from_name(&status);
+                    task
+                },
+            };
+            tasks.tasks.push(task);
+        }
+
+        Some(tasks)
+    }
+
+    fn save(&mut self, tasks: &SaveTasks) {
+        let transaction = self.connection.transaction().expect("データベースに書き込めませんでした");
+        transaction.execute("DELETE FROM tasks", []).expect("データベースに書き込めませんでした");
+        for (position, task) in tasks.tasks.iter().enumerate() {
+            upsert_task(&transaction, task, position);
+        }
+        transaction.commit().expect("データベースに書き込めませんでした");
+    }
+
+    fn apply(&mut self, changes: &[Change], tasks: &SaveTasks) {
+        // these touch every row anyway
+        let rewrite = changes.iter().any(|change| match change {
+            Change::Clear | Change::Sort(_) | Change::Replace => true,
+            _ => false,
+        });
+        if rewrite {
+            return self.save(tasks)
+        }
+
+        let transaction = self.connection.transaction().expect("データベースに書き込めませんでした");
+        for change in changes.iter() {
+            match change {
+                Change::Add(Task { id, .. }) | Change::Update(Task { id, .. }) | Change::Status(id, _) | Change::Edit(id, _) => {
+                    // write the latest version, it may have changed again since
+                    if let Some((position, task)) = tasks.tasks.iter().enumerate().find(|(_, task)| task.equals(id)) {
+                        upsert_task(&transaction, task, position);
+                    }
+                },
+                Change::Remove(id) => delete_task(&transaction, id),
+                _ => (),
+            }
+        }
+        transaction.commit().expect("データベースに書き込めませんでした");
+    }
+}
+
+fn upsert_task(connection: &Connection, task: &Task, position: usize) {
+    connection
+        .execute(
+            "INSERT INTO tasks (id, content, status, position, data) VALUES (?1, ?2, ?3, ?4, ?5)
+                ON CONFLICT(id) DO UPDATE SET content = excluded.content, status = excluded.status, position = excluded.position, data = excluded.data",
+            params![
+                task.id.to_string(),
+                task.content,
+                status_name(&task.status),
+                position as i64,
+                serde_json::to_string(task).expect("シリアライズできませんでした"),
+            ],
+        )
+        .expect("データベースに書き込めませんでした");
+}
+
+fn delete_task(connection: &Connection, id: &TaskId) {
+    connection
+        .execute("DELETE FROM tasks WHERE id = ?1", params![id.to_string()])
+        .expect("データベースに書き込めませんでした");
+}
+
+/// upgrade databases written by older versions
+fn migrate(connection: &Connection) -> rusqlite::Result<()> {
+    let version: i64 = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    let has_tasks: bool = connection
+        .query_row("SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'tasks'", [], |row| row.get(0))?;
+
+    if version < 2 && has_tasks {
+        connection.execute_batch(&format!("BEGIN; {} COMMIT;", MIGRATE_V1))?;
+    }
+    if version < 3 && has_tasks {
+        connection.execute_batch(MIGRATE_V2)?;
+    }
+    if version < 4 && has_tasks {
+        connection.execute_batch(MIGRATE_V3)?;
+    }
+    Ok(())
+}
+
+fn status_name(status: &TaskStatus) -> String {
+    format!("{:?}", status)
+}
+
+fn status_from_name(name: &str) -> TaskStatus {
+    TaskStatus::statuses()
+        .into_iter()
+        .find(|status| status_name(status) == name)
+        .unwrap_or(TaskStatus::New)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use super::*;
+
+    fn temp_path(name: &str, extension: &str) -> String {
+        let path = env::temp_dir().join(format!("tasking-{}-{}.{}", name, TaskId::generate(), extension));
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn migrate_from_json_only_on_first_use() {
+        let json = temp_path("migrate", "json");
+        let db = temp_path("migrate", "db");
+        let tasks = SaveTasks {
+            tasks: vec![Task::create(TaskId::generate(), "imported".to_string())],
+        };
+        JsonStore::new(&json).save(&tasks);
+
+        let mut store = SqliteStore::open(&db).expect("database");
+        assert!(store.migrate_from_json_once(&json));
+        assert_eq!(store.load().expect("tasks").tasks, tasks.tasks);
+
+        // cleared by the user, the old file stays out
+        store.save(&SaveTasks::new());
+        let mut store = SqliteStore::open(&db).expect("database");
+        assert!(!store.migrate_from_json_once(&json));
+        assert!(store.is_empty());
+
+        let _ = fs::remove_file(&json);
+        let _ = fs::remove_file(&db);
+    }
+
+    #[test]
+    fn open_refuses_other_files() {
+        let path = temp_path("not-sqlite", "db");
+        fs::write(&path, "{\"tasks\": []}").expect("file");
+
+        assert!(SqliteStore::open(&path).is_err());
+
+        let _ = fs::remove_file(&path);
+    }
+}
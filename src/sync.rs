@@ -0,0 +1,278 @@
+use druid::Selector;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::SyncConfig;
+use crate::i18n::{tr, tr_args};
+use crate::merge::Merge;
+use crate::model::SaveTasks;
+
+/// start pulling and pushing the data file
+pub const SYNC: Selector = Selector::new("tasking.sync.start");
+/// sync finished, the tasks it started from and the merged tasks or an error message
+pub const SYNC_DONE: Selector<(SaveTasks, Result<Merge, String>)> = Selector::new("tasking.sync.done");
+
+/// data directory as a git repository
+pub struct GitSync {
+    dir: PathBuf,
+    file: String,
+    remote: String,
+    branch: String,
+    // fallback identity when git has none configured
+    identity: Vec<String>,
+}
+
+impl GitSync {
+    /// init the repository next to the data file and set the remote
+    pub fn open(data_file: &str, config: &SyncConfig) -> Result<Self, String> {
+        let path = Path::new(data_file);
+        let dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        let file = path.file_name().and_then(|name| name.to_str()).ok_or_else(|| tr("sync-file-name-invalid"))?.to_string();
+
+        let mut sync = Self {
+            dir: dir,
+            file: file,
+            remote: config.remote.to_string(),
+            branch: config.branch.to_string(),
+            identity: Vec::new(),
+        };
+
+        if sync.git(&["rev-parse", "--git-dir"]).is_err() {
+            sync.git(&["init", "--quiet"])?;
+        }
+        if sync.git(&["config", "user.email"]).is_err() {
+            sync.identity = vec![
+                "-c".to_string(), "user.name=Tasking!".to_string(),
+                "-c".to_string(), "user.email=tasking@localhost".to_string(),
+            ];
+        }
+        match sync.git(&["remote", "get-url", "origin"]) {
+            Ok(url) if url.trim() == sync.remote => (),
+            Ok(_) => {
+                sync.git(&["remote", "set-url", "origin", sync.remote.as_str()])?;
+            },
+            Err(_) => {
+                sync.git(&["remote", "add", "origin", sync.remote.as_str()])?;
+            },
+        }
+
+        Ok(sync)
+    }
+
+    /// commit the data file, nothing happens if it is unchanged
+    pub fn commit(&self, message: &str) {
+        if self.git(&["add", "--", self.file.as_str()]).is_err() {
+            return
+        }
+        if self.git(&["diff", "--cached", "--quiet", "--", self.file.as_str()]).is_ok() {
+            return
+        }
+        if let Err(e) = self.git(&["commit", "--quiet", "-m", message, "--", self.file.as_str()]) {
+            eprintln!("コミットできませんでした: {}", e);
+        }
+    }
+
+    /// fetch the remote branch, false if it does not exist yet
+    pub fn fetch(&self) -> Result<bool, String> {
+        let heads = self.git(&["ls-remote", "--heads", "origin", self.branch.as_str()])?;
+        if heads.trim().is_empty() {
+            return Ok(false)
+        }
+
+        self.git(&["fetch", "--quiet", "origin", self.branch.as_str()])?;
+        Ok(true)
+    }
+
+    /// local commits already contain the remote branch
+    pub fn is_up_to_date(&self) -> bool {
+        self.git(&["merge-base", "--is-ancestor", self.remote_ref().as_str(), "HEAD"]).is_ok()
+    }
+
+    /// the remote branch only adds commits on top of ours
+    pub fn can_fast_forward(&self) -> bool {
+        self.git(&["rev-parse", "--verify", "--quiet", "HEAD"]).is_err()
+            || self.git(&["merge-base", "--is-ancestor", "HEAD", self.remote_ref().as_str()]).is_ok()
+    }
+
+    /// move to the remote branch
+    pub fn fast_forward(&self) -> Result<(), String> {
+        match self.git(&["rev-parse", "--verify", "--quiet", "HEAD"]) {
+            Ok(_) => self.git(&["merge", "--quiet", "--ff-only", self.remote_ref().as_str()]).map(|_| ()),
+            // nothing committed yet, start from the remote
+            Err(_) => self.git(&["checkout", "--quiet", "-f", "-B", self.branch.as_str(), self.remote_ref().as_str()]).map(|_| ()),
+        }
+    }
+
+    /// data file contents on the remote branch
+    pub fn remote_contents(&self) -> Option<String> {
+        self.git(&["show", format!("{}:{}", self.remote_ref(), self.file).as_str()]).ok()
+    }
+
+    /// data file contents at the common ancestor of local and remote
+    pub fn base_contents(&self) -> Option<String> {
+        let base = self.git(&["merge-base", "HEAD", self.remote_ref().as_str()]).ok()?;
+        self.git(&["show", format!("{}:{}", base.trim(), self.file).as_str()]).ok()
+    }
+
+    /// start a merge keeping our file, the caller writes the merged result
+    pub fn begin_merge(&self) -> Result<(), String> {
+        self.git(&["merge", "--quiet", "--no-ff", "--no-commit", "-s", "ours", self.remote_ref().as_str()]).map(|_| ())
+    }
+
+    /// commit the merged data file
+    pub fn finish_merge(&self, message: &str) -> Result<(), String> {
+        self.git(&["add", "--", self.file.as_str()])?;
+        self.git(&["commit", "--quiet", "-m", message]).map(|_| ())
+    }
+
+    /// push local commits
+    pub fn push(&self) -> Result<(), String> {
+        self.git(&["push", "--quiet", "origin", format!("HEAD:{}", self.branch).as_str()]).map(|_| ())
+    }
+
+    fn remote_ref(&self) -> String {
+        format!("origin/{}", self.branch)
+    }
+
+    fn git(&self, args: &[&str]) -> Result<String, String> {
+        let output = Command::new("git")
+            .args(&self.identity)
+            .args(args)
+            .current_dir(&self.dir)
+            .output()
+            .map_err(|e| tr_args("sync-git-failed", &[("error", &e)]))?;
+
+        match output.status.success() {
+            true => Ok(String::from_utf8_lossy(&output.stdout).to_string()),
+            false => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use super::*;
+    use crate::config::Storage;
+    use crate::model::{Task, TaskId, TaskRepository};
+
+    // a bare remote and the devices syncing with it
+    struct Remote {
+        root: PathBuf,
+    }
+
+    impl Remote {
+        fn new(name: &str) -> Self {
+            let root = env::temp_dir().join(format!("tasking-sync-{}-{}", name, TaskId::generate()));
+            fs::create_dir_all(&root).expect("folder");
+            let status = Command::new("git")
+                .args(["init", "--bare", "--quiet"])
+                .arg(root.join("remote.git"))
+                .status()
+                .expect("git");
+            assert!(status.success());
+
+            Self {
+                root: root,
+            }
+        }
+
+        fn device(&self, name: &str) -> TaskRepository {
+            let dir = self.root.join(name);
+            fs::create_dir_all(&dir).expect("folder");
+            let config = SyncConfig {
+                remote: self.root.join("remote.git").to_string_lossy().to_string(),
+                branch: "main".to_string(),
+            };
+            TaskRepository::open(dir.join("task.json").to_string_lossy().to_string(), Storage::Json)
+                .expect("repository")
+                .keep_backups(0)
+                .with_sync(&config)
+        }
+
+        fn has_branch(&self) -> bool {
+            let output = Command::new("git")
+                .args(["ls-remote", "--heads"])
+                .arg(self.root.join("remote.git"))
+                .arg("main")
+                .output()
+                .expect("git");
+            !output.stdout.is_empty()
+        }
+    }
+
+    impl Drop for Remote {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    fn tasks(tasks: &[&Task]) -> SaveTasks {
+        SaveTasks {
+            tasks: tasks.iter().map(|task| (*task).clone()).collect(),
+        }
+    }
+
+    #[test]
+    fn first_sync_pushes_to_an_empty_remote() {
+        let remote = Remote::new("push");
+        let device = remote.device("a");
+        device.save(tasks(&[&Task::create(TaskId::generate(), "task".to_string())]));
+
+        let merged = device.sync().expect("sync");
+        assert!(merged.is_clean());
+        assert!(remote.has_branch());
+    }
+
+    #[test]
+    fn sync_fast_forwards_to_remote_changes() {
+        let remote = Remote::new("fast-forward");
+        let a = remote.device("a");
+        let b = remote.device("b");
+        let first = Task::create(TaskId::generate(), "first".to_string());
+        a.save(tasks(&[&first]));
+        a.sync().expect("sync");
+
+        // nothing committed on b yet
+        assert_eq!(b.sync().expect("sync").tasks.tasks, vec![first.clone()]);
+
+        let second = Task::create(TaskId::generate(), "second".to_string());
+        a.save(tasks(&[&first, &second]));
+        a.sync().expect("sync");
+
+        let merged = b.sync().expect("sync");
+        assert!(merged.is_clean());
+        assert_eq!(b.load().tasks, vec![first, second]);
+    }
+
+    #[test]
+    fn diverged_edits_merge_with_a_conflict() {
+        let remote = Remote::new("merge");
+        let a = remote.device("a");
+        let b = remote.device("b");
+        let task = Task::create(TaskId::generate(), "task".to_string());
+        a.save(tasks(&[&task]));
+        a.sync().expect("sync");
+        b.sync().expect("sync");
+
+        let mut edited_a = task.clone();
+        edited_a.edit("a".to_string());
+        a.save(tasks(&[&edited_a]));
+        a.sync().expect("sync");
+
+        let mut edited_b = task.clone();
+        edited_b.edit("b".to_string());
+        let added_b = Task::create(TaskId::generate(), "added".to_string());
+        b.save(tasks(&[&edited_b, &added_b]));
+
+        // this device keeps its edit, the merge is pushed
+        let merged = b.sync().expect("sync");
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(b.load().tasks, vec![edited_b.clone(), added_b.clone()]);
+
+        a.sync().expect("sync");
+        assert_eq!(a.load().tasks, vec![edited_b, added_b]);
+    }
+}
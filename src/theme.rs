@@ -0,0 +1,257 @@
+use druid::{Color, Data, Env, Key};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::fs;
+
+use crate::config::app_path;
+use crate::i18n::tr;
+use crate::model::TaskStatus;
+
+// user theme json file next to the executable
+const THEME_FILENAME: &str = "theme.json";
+
+// colors read by the widgets
+pub const BACKGROUND: Key<Color> = Key::new("tasking.theme.background");
+pub const TEXT: Key<Color> = Key::new("tasking.theme.text");
+pub const MUTED: Key<Color> = Key::new("tasking.theme.muted");
+pub const FAINT: Key<Color> = Key::new("tasking.theme.faint");
+pub const SURFACE: Key<Color> = Key::new("tasking.theme.surface");
+pub const ON_ACCENT: Key<Color> = Key::new("tasking.theme.on-accent");
+pub const PRIMARY: Key<Color> = Key::new("tasking.theme.primary");
+pub const SECONDARY: Key<Color> = Key::new("tasking.theme.secondary");
+pub const DARK: Key<Color> = Key::new("tasking.theme.dark");
+pub const SUCCESS: Key<Color> = Key::new("tasking.theme.success");
+pub const WARNING: Key<Color> = Key::new("tasking.theme.warning");
+pub const DANGER: Key<Color> = Key::new("tasking.theme.danger");
+pub const STATUS_NEW: Key<Color> = Key::new("tasking.theme.status-new");
+pub const STATUS_PROGRESS: Key<Color> = Key::new("tasking.theme.status-progress");
+pub const STATUS_STOP: Key<Color> = Key::new("tasking.theme.status-stop");
+pub const STATUS_DONE: Key<Color> = Key::new("tasking.theme.status-done");
+/// keyboard focus ring, drawn on the window background
+pub const FOCUS: Key<Color> = Key::new("tasking.theme.focus");
+
+/// selectable themes, custom is read from theme.json
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default, Data)]
+pub enum ThemeName {
+    #[default]
+    Light,
+    Dark,
+    HighContrast,
+    Custom,
+}
+
+impl ThemeName {
+    pub fn names() -> Vec<Self> {
+        vec![Self::Light, Self::Dark, Self::HighContrast, Self::Custom]
+    }
+}
+
+impl fmt::Display for ThemeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::Light => tr("theme-light"),
+            Self::Dark => tr("theme-dark"),
+            Self::HighContrast => tr("theme-high-contrast"),
+            Self::Custom => tr("theme-custom"),
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// colors of the app, missing entries in theme.json fall back to the light ones
+#[derive(Deserialize, Debug, Clone, Data)]
+#[serde(default)]
+pub struct Theme {
+    #[serde(skip)]
+    pub name: ThemeName,
+    #[serde(deserialize_with = "color")]
+    background: Color,
+    #[serde(deserialize_with = "color")]
+    text: Color,
+    #[serde(deserialize_with = "color")]
+    muted: Color,
+    #[serde(deserialize_with = "color")]
+    faint: Color,
+    #[serde(deserialize_with = "color")]
+    border: Color,
+    #[serde(deserialize_with = "color")]
+    surface: Color,
+    #[serde(deserialize_with = "color")]
+    on_accent: Color,
+    #[serde(deserialize_with = "color")]
+    primary: Color,
+    #[serde(deserialize_with = "color")]
+    secondary: Color,
+    #[serde(deserialize_with = "color")]
+    dark: Color,
+    #[serde(deserialize_with = "color")]
+    success: Color,
+    #[serde(deserialize_with = "color")]
+    warning: Color,
+    #[serde(deserialize_with = "color")]
+    danger: Color,
+    #[serde(deserialize_with = "color")]
+    status_new: Color,
+    #[serde(deserialize_with = "color")]
+    status_progress: Color,
+    #[serde(deserialize_with = "color")]
+    status_stop: Color,
+    #[serde(deserialize_with = "color")]
+    status_done: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+impl Theme {
+    /// preset or user theme
+    pub fn load(name: ThemeName) -> Self {
+        let theme = match name {
+            ThemeName::Light => Self::light(),
+            ThemeName::Dark => Self::dark(),
+            ThemeName::HighContrast => Self::high_contrast(),
+            ThemeName::Custom => Self::from_file(&app_path(THEME_FILENAME)),
+        };
+
+        Self {
+            name: name,
+            ..theme
+        }
+    }
+
+    fn from_file(path: &str) -> Self {
+        let serialized = match fs::read_to_string(path) {
+            Ok(serialized) => serialized,
+            Err(e) => {
+                eprintln!("{} ファイルを読み込めませんでした: {}", path, e);
+                return Self::light();
+            },
+        };
+
+        match serde_json::from_str::<Theme>(&serialized) {
+            Ok(theme) => theme,
+            Err(e) => {
+                eprintln!("{} ファイルのテーマが正しくありません: {}", path, e);
+                Self::light()
+            },
+        }
+    }
+
+    fn light() -> Self {
+        Self {
+            name: ThemeName::Light,
+            background: Color::WHITE,
+            text: Color::BLACK,
+            muted: Color::grey8(108),
+            faint: Color::grey8(190),
+            border: Color::grey8(58),
+            surface: Color::WHITE,
+            on_accent: Color::WHITE,
+            primary: Color::rgb8(0, 123, 255),
+            secondary: Color::rgb8(108, 117, 125),
+            dark: Color::rgb8(88, 97, 105),
+            success: Color::rgb8(40, 167, 69),
+            warning: Color::rgb8(255, 193, 7),
+            danger: Color::rgb8(220, 53, 69),
+            status_new: Color::rgb8(23, 162, 184),
+            status_progress: Color::rgb8(40, 167, 69),
+            status_stop: Color::rgb8(108, 117, 125),
+            status_done: Color::rgb8(255, 0, 0),
+        }
+    }
+
+    fn dark() -> Self {
+        Self {
+            name: ThemeName::Dark,
+            background: Color::grey8(32),
+            text: Color::grey8(230),
+            muted: Color::grey8(160),
+            faint: Color::grey8(90),
+            border: Color::grey8(110),
+            surface: Color::grey8(48),
+            on_accent: Color::WHITE,
+            primary: Color::rgb8(55, 140, 240),
+            secondary: Color::rgb8(100, 108, 116),
+            dark: Color::rgb8(70, 78, 86),
+            success: Color::rgb8(46, 160, 67),
+            warning: Color::rgb8(200, 150, 0),
+            danger: Color::rgb8(210, 70, 80),
+            status_new: Color::rgb8(30, 140, 160),
+            status_progress: Color::rgb8(46, 160, 67),
+            status_stop: Color::rgb8(100, 108, 116),
+            status_done: Color::rgb8(210, 50, 50),
+        }
+    }
+
+    fn high_contrast() -> Self {
+        Self {
+            name: ThemeName::HighContrast,
+            background: Color::BLACK,
+            text: Color::WHITE,
+            muted: Color::grey8(220),
+            faint: Color::grey8(150),
+            border: Color::WHITE,
+            surface: Color::BLACK,
+            on_accent: Color::BLACK,
+            primary: Color::rgb8(0, 220, 255),
+            secondary: Color::grey8(200),
+            dark: Color::grey8(230),
+            success: Color::rgb8(0, 255, 0),
+            warning: Color::rgb8(255, 255, 0),
+            danger: Color::rgb8(255, 110, 110),
+            status_new: Color::rgb8(0, 255, 255),
+            status_progress: Color::rgb8(0, 255, 0),
+            status_stop: Color::grey8(200),
+            status_done: Color::rgb8(255, 110, 110),
+        }
+    }
+
+    /// set the theme keys and the druid colors they replace
+    pub fn configure(&self, env: &mut Env) {
+        env.set(BACKGROUND, self.background.clone());
+        env.set(TEXT, self.text.clone());
+        env.set(MUTED, self.muted.clone());
+        env.set(FAINT, self.faint.clone());
+        env.set(SURFACE, self.surface.clone());
+        env.set(ON_ACCENT, self.on_accent.clone());
+        env.set(PRIMARY, self.primary.clone());
+        env.set(SECONDARY, self.secondary.clone());
+        env.set(DARK, self.dark.clone());
+        env.set(SUCCESS, self.success.clone());
+        env.set(WARNING, self.warning.clone());
+        env.set(DANGER, self.danger.clone());
+        env.set(STATUS_NEW, self.status_new.clone());
+        env.set(STATUS_PROGRESS, self.status_progress.clone());
+        env.set(STATUS_STOP, self.status_stop.clone());
+        env.set(STATUS_DONE, self.status_done.clone());
+        env.set(FOCUS, self.text.clone());
+
+        env.set(druid::theme::WINDOW_BACKGROUND_COLOR, self.background.clone());
+        env.set(druid::theme::CURSOR_COLOR, self.text.clone());
+        env.set(druid::theme::TEXT_COLOR, self.text.clone());
+        env.set(druid::theme::PLACEHOLDER_COLOR, self.muted.clone());
+        env.set(druid::theme::BORDER_DARK, self.border.clone());
+        // text boxes show the window background
+        env.set(druid::theme::BACKGROUND_LIGHT, self.background.clone().with_alpha(0.0));
+    }
+}
+
+/// color of a status
+pub fn status_color(status: &TaskStatus) -> Key<Color> {
+    match status {
+        TaskStatus::New => STATUS_NEW,
+        TaskStatus::Progress => STATUS_PROGRESS,
+        TaskStatus::Stop => STATUS_STOP,
+        TaskStatus::Done => STATUS_DONE,
+    }
+}
+
+// "#rrggbb" or "#rrggbbaa"
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    Color::from_hex_str(&text).map_err(|e| D::Error::custom(format!("{}: {}", text, e)))
+}
@@ -0,0 +1,53 @@
+use chrono::{Duration as ChronoDuration, Utc};
+use druid::widget::prelude::*;
+use druid::widget::Controller;
+use druid::TimerToken;
+use std::time::Duration;
+
+use crate::TaskState;
+
+const TICK: Duration = Duration::from_secs(1);
+
+/// moves the clock forward while a task is running, so tracked time counts up
+pub struct Clock {
+    timer: TimerToken,
+}
+
+impl Clock {
+    /// construct
+    pub fn new() -> Self {
+        Self {
+            timer: TimerToken::INVALID,
+        }
+    }
+}
+
+impl<W: Widget<TaskState>> Controller<TaskState, W> for Clock {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut TaskState, env: &Env) {
+        match event {
+            Event::WindowConnected => {
+                self.timer = ctx.request_timer(TICK);
+            },
+            Event::Timer(token) if *token == self.timer => {
+                if data.pomodoro.is_active() || data.tasks.tasks.iter().any(|task| task.is_running()) {
+                    data.now = Utc::now();
+                    data.tick_pomodoro();
+                }
+                self.timer = ctx.request_timer(TICK);
+                return;
+            },
+            _ => (),
+        }
+
+        child.event(ctx, event, data, env)
+    }
+}
+
+/// tracked time as h:mm:ss or m:ss
+pub fn format_duration(duration: ChronoDuration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    match seconds / 3600 {
+        0 => format!("{}:{:02}", seconds / 60, seconds % 60),
+        hours => format!("{}:{:02}:{:02}", hours, seconds % 3600 / 60, seconds % 60),
+    }
+}
@@ -0,0 +1,90 @@
+use druid::im::Vector;
+use druid::{Data, ExtEventSink, Selector, Target};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::merge::Conflict;
+use crate::model::{SaveTasks, TaskRepository, Tasks};
+
+/// data file was changed outside of this app
+pub const EXTERNAL_CHANGE: Selector<SaveTasks> = Selector::new("tasking.watch.external-change");
+
+/// resolve local and external changes
+pub const RESOLVE_CONFLICT: Selector<Resolution> = Selector::new("tasking.watch.resolve-conflict");
+
+/// which side of a conflict to keep
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resolution {
+    Theirs,
+    Mine,
+    // both sides, mine where they conflict
+    Merged,
+}
+
+/// external changes that could not be merged cleanly
+#[derive(Debug, Clone, Data)]
+pub struct ExternalConflict {
+    pub theirs: Tasks,
+    pub merged: Tasks,
+    pub conflicts: Vector<Conflict>,
+}
+
+impl ExternalConflict {
+    /// one line per conflicting task
+    pub fn summary(&self) -> String {
+        self.conflicts.iter().map(|conflict| conflict.to_string()).collect::<Vec<String>>().join("\n")
+    }
+}
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// data file watcher, stops when dropped
+pub struct Watcher {
+    stopped: Arc<AtomicBool>,
+}
+
+impl Watcher {
+    /// start polling the repository file
+    pub fn start(repository: TaskRepository, sink: ExtEventSink) -> Self {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let stop = stopped.clone();
+
+        thread::spawn(move || {
+            let mut modified = modified_time(repository.filename());
+
+            while !stop.load(Ordering::SeqCst) {
+                thread::sleep(POLL_INTERVAL);
+
+                let current = modified_time(repository.filename());
+                if current == modified {
+                    continue;
+                }
+                modified = current;
+
+                if let Some(tasks) = repository.read_changed() {
+                    if sink.submit_command(EXTERNAL_CHANGE, tasks, Target::Auto).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Self {
+            stopped: stopped,
+        }
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+fn modified_time(filename: &str) -> Option<SystemTime> {
+    fs::metadata(Path::new(filename)).and_then(|meta| meta.modified()).ok()
+}
@@ -0,0 +1,162 @@
+use druid::widget::prelude::*;
+use druid::widget::{Label, LabelText, LineBreaking};
+use druid::{theme, Color, KbKey, KeyOrValue, Rect, UnitPoint, WidgetPod};
+
+use crate::theme::FOCUS;
+
+// fill strength while pressed and hovered
+const PRESSED_ALPHA: f64 = 0.7;
+const HOT_ALPHA: f64 = 0.82;
+const FOCUS_RING_WIDTH: f64 = 2.0;
+
+/// filled button, clicked with the mouse or with Enter / Space while focused
+pub struct Button<T> {
+    label: WidgetPod<T, Label<T>>,
+    color: Box<dyn Fn(&T, &Env) -> Color>,
+    // where the label sits in the button
+    align: UnitPoint,
+    action: Option<Box<dyn Fn(&mut EventCtx, &mut T, &Env)>>,
+}
+
+impl<T: Data> Button<T> {
+    /// construct with a fill color picked from the data
+    pub fn new(label: impl Into<LabelText<T>>, color: impl Fn(&T, &Env) -> Color + 'static) -> Self {
+        Self {
+            label: WidgetPod::new(Label::new(label)),
+            color: Box::new(color),
+            align: UnitPoint::CENTER,
+            action: None,
+        }
+    }
+
+    pub fn with_text_size(mut self, size: impl Into<KeyOrValue<f64>>) -> Self {
+        self.label.widget_mut().set_text_size(size);
+        self
+    }
+
+    pub fn with_text_color(mut self, color: impl Into<KeyOrValue<Color>>) -> Self {
+        self.label.widget_mut().set_text_color(color);
+        self
+    }
+
+    pub fn with_line_break_mode(mut self, mode: LineBreaking) -> Self {
+        self.label.widget_mut().set_line_break_mode(mode);
+        self
+    }
+
+    /// place the label other than centered
+    pub fn with_align(mut self, align: UnitPoint) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// run on click and on keyboard activation
+    pub fn on_click(mut self, action: impl Fn(&mut EventCtx, &mut T, &Env) + 'static) -> Self {
+        self.action = Some(Box::new(action));
+        self
+    }
+
+    fn activate(&self, ctx: &mut EventCtx, data: &mut T, env: &Env) {
+        if let Some(action) = &self.action {
+            action(ctx, data, env);
+        }
+    }
+}
+
+fn is_activation(key: &KbKey) -> bool {
+    match key {
+        KbKey::Enter => true,
+        KbKey::Character(text) => text == " ",
+        _ => false,
+    }
+}
+
+impl<T: Data> Widget<T> for Button<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        match event {
+            Event::MouseDown(mouse) if mouse.button.is_left() => {
+                ctx.set_active(true);
+                ctx.request_paint();
+            },
+            Event::MouseUp(_) if ctx.is_active() => {
+                ctx.set_active(false);
+                ctx.request_paint();
+                if ctx.is_hot() {
+                    self.activate(ctx, data, env);
+                }
+            },
+            Event::KeyDown(key) if ctx.is_focused() => {
+                match &key.key {
+                    KbKey::Tab if key.mods.shift() => ctx.focus_prev(),
+                    KbKey::Tab => ctx.focus_next(),
+                    // pressed until the key is released
+                    other if is_activation(other) => ctx.set_active(true),
+                    _ => return,
+                }
+                ctx.request_paint();
+                ctx.set_handled();
+            },
+            Event::KeyUp(key) if ctx.is_focused() && ctx.is_active() && is_activation(&key.key) => {
+                ctx.set_active(false);
+                ctx.request_paint();
+                ctx.set_handled();
+                self.activate(ctx, data, env);
+            },
+            _ => (),
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        match event {
+            LifeCycle::WidgetAdded => ctx.register_for_focus(),
+            LifeCycle::HotChanged(_) | LifeCycle::FocusChanged(_) => ctx.request_paint(),
+            _ => (),
+        }
+
+        self.label.lifecycle(ctx, event, data, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
+        if !old_data.same(data) || ctx.env_changed() {
+            ctx.request_paint();
+        }
+
+        self.label.update(ctx, data, env)
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        // fill the given space like a centered label did
+        let label = self.label.layout(ctx, &bc.loosen(), data, env);
+        let size = bc.constrain(Size::new(
+            if bc.is_width_bounded() { bc.max().width } else { label.width },
+            if bc.is_height_bounded() { bc.max().height } else { label.height },
+        ));
+
+        let origin = self.align.resolve(Rect::from_origin_size((0.0, 0.0), size - label));
+        self.label.set_origin(ctx, origin);
+        // the focus ring is drawn around the bounds
+        ctx.set_paint_insets(FOCUS_RING_WIDTH);
+        size
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &T, env: &Env) {
+        let radius = env.get(theme::BUTTON_BORDER_RADIUS);
+        let bounds = ctx.size().to_rounded_rect(radius);
+
+        let color = (self.color)(data, env);
+        let alpha = match (ctx.is_active(), ctx.is_hot()) {
+            (true, _) => PRESSED_ALPHA,
+            (false, true) => HOT_ALPHA,
+            (false, false) => 1.0,
+        };
+        ctx.fill(bounds, &color.with_alpha(alpha));
+
+        if ctx.is_focused() {
+            // centered on half the width so the stroke stays within the paint insets
+            let ring = ctx.size().to_rect().inset(-FOCUS_RING_WIDTH / 2.0).to_rounded_rect(radius);
+            ctx.stroke(ring, &env.get(FOCUS), FOCUS_RING_WIDTH);
+        }
+
+        self.label.paint(ctx, data, env)
+    }
+}
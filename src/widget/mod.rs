@@ -0,0 +1,5 @@
+mod button;
+mod password;
+
+pub use button::Button;
+pub use password::password_box;
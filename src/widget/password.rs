@@ -0,0 +1,64 @@
+use druid::widget::prelude::*;
+use druid::widget::{Controller, Label, Painter};
+use druid::{theme, KbKey, KeyOrValue, Selector, WidgetExt};
+
+/// masked text entry, submits a command on Enter
+struct PasswordController {
+    submit: Selector,
+}
+
+impl<W: Widget<String>> Controller<String, W> for PasswordController {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut String, env: &Env) {
+        match event {
+            Event::WindowConnected | Event::MouseDown(_) => {
+                ctx.request_focus();
+            },
+            Event::KeyDown(key) if ctx.is_focused() => {
+                match &key.key {
+                    KbKey::Enter => ctx.submit_command(self.submit),
+                    KbKey::Backspace => {
+                        data.pop();
+                    },
+                    KbKey::Character(text) if !key.mods.ctrl() && !key.mods.meta() => data.push_str(text),
+                    _ => return child.event(ctx, event, data, env),
+                }
+                ctx.set_handled();
+                return;
+            },
+            _ => (),
+        }
+
+        child.event(ctx, event, data, env)
+    }
+
+    fn lifecycle(&mut self, child: &mut W, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &String, env: &Env) {
+        match event {
+            LifeCycle::WidgetAdded => ctx.register_for_focus(),
+            LifeCycle::FocusChanged(_) => ctx.request_paint(),
+            _ => (),
+        }
+
+        child.lifecycle(ctx, event, data, env)
+    }
+}
+
+/// passphrase field showing a dot per character
+pub fn password_box(submit: Selector, text_size: impl Into<KeyOrValue<f64>>) -> impl Widget<String> {
+    let painter = Painter::new(|ctx, _, env| {
+        let bounds = ctx.size().to_rect().inset(-0.5).to_rounded_rect(env.get(theme::TEXTBOX_BORDER_RADIUS));
+        let color = match ctx.is_focused() {
+            true => env.get(theme::PRIMARY_LIGHT),
+            false => env.get(theme::BORDER_DARK),
+        };
+        ctx.stroke(bounds.inset(-0.5), &color, 1.0);
+    });
+
+    Label::new(|data: &String, _: &Env| "●".repeat(data.chars().count()))
+        .with_text_size(text_size)
+        .padding(8.5)
+        .expand_width()
+        .background(painter)
+        .controller(PasswordController {
+            submit: submit,
+        })
+}